@@ -0,0 +1,185 @@
+// Power spectrum of a regularly sampled series as (frequency, power) pairs.
+// The linear trend is removed first so drift does not drown out the cycles.
+pub fn power_spectrum(data: &[(f64, f64)]) -> Result<Vec<(f64, f64)>, String> {
+    if data.len() < 4 {
+        return Err("Need at least 4 points for a spectrum".to_string());
+    }
+
+    let dx = data[1].0 - data[0].0;
+    if dx <= 0.0 {
+        return Err("Series is not regularly sampled".to_string());
+    }
+    let tolerance = dx * 1e-6;
+    if data.windows(2).any(|w| ((w[1].0 - w[0].0) - dx).abs() > tolerance) {
+        return Err("Series is not regularly sampled".to_string());
+    }
+
+    let (slope, intercept) = linear_fit(data);
+    let n = data.len().next_power_of_two();
+    let mut buf: Vec<(f64, f64)> = data.iter().map(|&(x, y)| (y - (slope * x + intercept), 0.0)).collect();
+    buf.resize(n, (0.0, 0.0));
+    fft(&mut buf);
+
+    let spectrum = (1..=n / 2)
+        .map(|k| {
+            let (re, im) = buf[k];
+            (k as f64 / (n as f64 * dx), (re * re + im * im) / data.len() as f64)
+        })
+        .collect();
+    Ok(spectrum)
+}
+
+// Mean of the points less than `span` before the last one, data sorted by x
+pub fn window_mean(data: &[(f64, f64)], span: f64) -> Option<f64> {
+    let &(last, _) = data.last()?;
+    let start = data.partition_point(|&(x, _)| x <= last - span);
+    let window = &data[start..];
+    Some(window.iter().map(|&(_, y)| y).sum::<f64>() / window.len() as f64)
+}
+
+// Mean at each point of the points less than `span` before it, data sorted by x
+pub fn moving_average(data: &[(f64, f64)], span: f64) -> Vec<(f64, f64)> {
+    let mut start = 0;
+    let mut sum = 0.0;
+    data.iter().enumerate()
+        .map(|(i, &(x, y))| {
+            sum += y;
+            while data[start].0 <= x - span {
+                sum -= data[start].1;
+                start += 1;
+            }
+            (x, sum / (i + 1 - start) as f64)
+        })
+        .collect()
+}
+
+// Last y minus the y `span` earlier, taken from the latest point at or before
+// that x, or the first point when the data does not reach back that far
+pub fn window_change(data: &[(f64, f64)], span: f64) -> Option<f64> {
+    let &(last, y) = data.last()?;
+    let before = data.partition_point(|&(x, _)| x <= last - span);
+    let (_, from) = data[before.saturating_sub(1)];
+    Some(y - from)
+}
+
+// Range a new y at x is expected in, from a linear fit of the last points
+// before x. Wide enough that only gross errors like a missing digit fall out
+pub fn expected_range(data: &[(f64, f64)], x: f64) -> Option<(f64, f64)> {
+    let end = data.partition_point(|&(px, _)| px < x);
+    let recent = &data[end.saturating_sub(10)..end];
+    if recent.len() < 5 {
+        return None;
+    }
+
+    let (slope, intercept) = linear_fit(recent);
+    let residual = (recent.iter()
+        .map(|&(px, py)| (py - (slope * px + intercept)).powi(2))
+        .sum::<f64>() / recent.len() as f64).sqrt();
+    let predicted = slope * x + intercept;
+    let half = (4.0 * residual).max(0.25 * predicted.abs()).max(f64::EPSILON);
+    Some((predicted - half, predicted + half))
+}
+
+// Usual distance between consecutive x, ignoring points that share an x
+pub fn median_step(data: &[(f64, f64)]) -> Option<f64> {
+    let mut steps: Vec<f64> = data.windows(2).map(|w| w[1].0 - w[0].0).filter(|&d| d > 0.0).collect();
+    if steps.is_empty() {
+        return None;
+    }
+    steps.sort_by(|a, b| a.total_cmp(b));
+    Some(steps[steps.len() / 2])
+}
+
+// Population standard deviation, None without values
+pub fn std_dev(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    Some((values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64).sqrt())
+}
+
+// Count of values between each pair of consecutive edges, the last bin
+// also takes values on its upper edge. Values outside the edges are left out
+pub fn histogram(values: &[f64], edges: &[f64]) -> Vec<usize> {
+    let mut counts = vec![0; edges.len().saturating_sub(1)];
+    for &v in values {
+        let i = edges.partition_point(|&e| e <= v);
+        match (i, edges.last()) {
+            (0, _) => {}
+            (_, Some(&last)) if v == last => counts[edges.len() - 2] += 1,
+            (i, _) if i < edges.len() => counts[i - 1] += 1,
+            _ => {}
+        }
+    }
+    counts
+}
+
+// Least squares fit y = slope * x + intercept
+pub fn linear_fit(data: &[(f64, f64)]) -> (f64, f64) {
+    let n = data.len() as f64;
+    let x_mean = data.iter().map(|&(x, _)| x).sum::<f64>() / n;
+    let y_mean = data.iter().map(|&(_, y)| y).sum::<f64>() / n;
+
+    let mut sxy = 0.0;
+    let mut sxx = 0.0;
+    for &(x, y) in data {
+        sxy += (x - x_mean) * (y - y_mean);
+        sxx += (x - x_mean) * (x - x_mean);
+    }
+
+    let slope = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+    (slope, y_mean - slope * x_mean)
+}
+
+// Evenly spaced ticks on round numbers (1, 2, 2.5 or 5 times a power of ten)
+// covering [min, max] in about max_ticks steps
+pub fn nice_ticks(min: f64, max: f64, max_ticks: usize) -> Vec<f64> {
+    let (min, max) = if max > min { (min, max) } else { (min - 1.0, max + 1.0) };
+    let raw = (max - min) / max_ticks.max(1) as f64;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 2.5, 5.0, 10.0].iter()
+        .map(|m| m * magnitude)
+        .find(|&s| s >= raw * (1.0 - 1e-9))
+        .unwrap_or(10.0 * magnitude);
+
+    let lo = (min / step + 1e-9).floor();
+    let hi = (max / step - 1e-9).ceil();
+    // + 0.0 turns -0 into 0
+    (0..=(hi - lo) as usize).map(|i| (lo + i as f64) * step + 0.0).collect()
+}
+
+// In-place iterative radix-2 FFT, buf.len() must be a power of two
+fn fft(buf: &mut [(f64, f64)]) {
+    let n = buf.len();
+
+    // Bit reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            buf.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * std::f64::consts::PI / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (w_re, w_im) = ((angle * k as f64).cos(), (angle * k as f64).sin());
+                let (a_re, a_im) = buf[start + k];
+                let (b_re, b_im) = buf[start + k + len / 2];
+                let (t_re, t_im) = (b_re * w_re - b_im * w_im, b_re * w_im + b_im * w_re);
+                buf[start + k] = (a_re + t_re, a_im + t_im);
+                buf[start + k + len / 2] = (a_re - t_re, a_im - t_im);
+            }
+        }
+        len <<= 1;
+    }
+}
@@ -0,0 +1,286 @@
+use std::{error::Error, fs};
+use serde::{Serialize, Deserialize};
+
+use ratatui::symbols;
+
+pub const CONFIG_PATH: &str = "tracktui.toml";
+
+pub const MAX_KPIS: usize = 5;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    // Maximum redraws per second, input arriving faster is batched into one frame.
+    // Fewer are drawn while redrawing is slow
+    pub refresh_rate: u32,
+
+    // Row order of the table, storage is always sorted by x
+    pub table_order: SortOrder,
+
+    // Decimals shown for table values, trailing zeros are dropped
+    pub precision: usize,
+
+    // Amount + and - add to or take from the y of the selected table row
+    pub nudge_step: f64,
+
+    // Ask before inserting a y far off the recent trend
+    pub trend_check: bool,
+
+    // Minutes between automatic saves, 0 only saves on exit
+    pub autosave: u32,
+
+    // View shown on startup
+    pub default_view: ViewMode,
+
+    // Glyphs the graph lines are drawn with
+    pub marker: MarkerStyle,
+
+    // Colors from themes/<name>.toml, empty uses the built-in ones
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub theme: String,
+
+    // Ease the graph between series and zoom levels, and scroll the table smoothly
+    pub animations: bool,
+
+    // Capture the mouse so a click in the graph fills the insert bar.
+    // The terminal's own text selection needs SHIFT while it is on
+    pub mouse: bool,
+
+    // Zone times of day are shown and entered in, like Europe/Berlin, so they
+    // stay put when the system's zone changes. Empty follows the system.
+    // Points are stored in UTC either way
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub timezone: String,
+
+    // Steps a clicked x and y are rounded to, x counts days for dates, 0 keeps the precision
+    pub click_step_x: f64,
+    pub click_step_y: f64,
+
+    // Series shown with their latest value in a strip above the graph, at most MAX_KPIS
+    pub kpis: Vec<String>,
+
+    // Bearer token of the --serve API, the API stays off while empty. It is
+    // sent in the clear, so serve on 127.0.0.1 behind a tunnel or TLS proxy
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub api_token: String,
+
+    // Shell commands run on events, see Hooks
+    pub hooks: Hooks,
+
+    // Stats over the last days before the latest point, for the stats panel
+    pub rolling: Vec<RollingStat>,
+
+    // User-defined templates, listed after the built-in ones
+    pub templates: Vec<SeriesTemplate>,
+
+    // Column mappings saved by :import, matched by the file's header
+    pub import_profiles: Vec<ImportProfile>,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    NewestFirst,
+    OldestFirst,
+}
+
+impl SortOrder {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortOrder::NewestFirst => " newest first ",
+            SortOrder::OldestFirst => " oldest first ",
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkerStyle {
+    #[default]
+    Braille,
+    Dot,
+    Block,
+    HalfBlock,
+}
+
+impl MarkerStyle {
+    pub const ALL: [MarkerStyle; 4] = [MarkerStyle::Braille, MarkerStyle::Dot, MarkerStyle::Block, MarkerStyle::HalfBlock];
+
+    pub fn symbol(&self) -> symbols::Marker {
+        match self {
+            MarkerStyle::Braille => symbols::Marker::Braille,
+            MarkerStyle::Dot => symbols::Marker::Dot,
+            MarkerStyle::Block => symbols::Marker::Block,
+            MarkerStyle::HalfBlock => symbols::Marker::HalfBlock,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MarkerStyle::Braille => "braille",
+            MarkerStyle::Dot => "dot",
+            MarkerStyle::Block => "block",
+            MarkerStyle::HalfBlock => "half block",
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ViewMode {
+    #[default]
+    Graph,
+    Table,
+    Spectrum,
+    Menu,
+    Help,
+    Settings,
+    Compare,
+    Batch,
+    Series,
+    Trash,
+}
+
+impl ViewMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ViewMode::Graph => "graph",
+            ViewMode::Table => "table",
+            ViewMode::Spectrum => "spectrum",
+            ViewMode::Menu => "menu",
+            ViewMode::Help => "help",
+            ViewMode::Settings => "settings",
+            ViewMode::Compare => "compare",
+            ViewMode::Batch => "batch entry",
+            ViewMode::Series => "series",
+            ViewMode::Trash => "trash",
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum YAxis {
+    #[default]
+    Zero, // From zero up to the largest value
+    Fit, // Padded around the data
+    Log, // Powers of ten apart, values at or below zero are left out
+}
+
+// Commands get the event's data as arguments, $1 is always the series name
+// except for on_save, which gets the data file
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Hooks {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub on_point_added: String, // series x y
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub on_save: String, // path, then the series whose points changed
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub on_goal_reached: String, // series x y goal
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct RollingStat {
+    pub kind: RollingKind,
+    pub days: f64, // Plain x values count as days
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RollingKind {
+    Mean,
+    Change,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            refresh_rate: 30,
+            table_order: SortOrder::default(),
+            precision: 4,
+            nudge_step: 0.1,
+            trend_check: true,
+            autosave: 0,
+            default_view: ViewMode::Graph,
+            marker: MarkerStyle::default(),
+            theme: String::new(),
+            animations: true,
+            mouse: false,
+            timezone: String::new(),
+            click_step_x: 1.0,
+            click_step_y: 0.0,
+            kpis: Vec::new(),
+            api_token: String::new(),
+            hooks: Hooks::default(),
+            rolling: vec![
+                RollingStat { kind: RollingKind::Mean, days: 7.0 },
+                RollingStat { kind: RollingKind::Mean, days: 30.0 },
+                RollingStat { kind: RollingKind::Change, days: 7.0 },
+            ],
+            templates: Vec::new(),
+            import_profiles: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SeriesTemplate {
+    pub name: String,
+    pub unit: String,
+    pub columns: Vec<String>,
+    pub goal: Option<f64>,
+    pub y_axis: YAxis,
+}
+
+impl SeriesTemplate {
+    fn builtin() -> Vec<Self> {
+        vec![
+            Self { name: "Weight".to_string(), unit: "kg".to_string(), y_axis: YAxis::Fit, ..Default::default() },
+            Self { name: "Running".to_string(), unit: "km".to_string(), goal: Some(5.0), ..Default::default() },
+            Self { name: "Expenses".to_string(), unit: "EUR".to_string(), ..Default::default() },
+            Self { name: "Habit".to_string(), unit: "done".to_string(), goal: Some(1.0), ..Default::default() },
+            Self {
+                name: "Blood pressure".to_string(),
+                unit: "mmHg".to_string(),
+                columns: vec!["sys".to_string(), "dia".to_string()],
+                y_axis: YAxis::Fit,
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+// Columns to take x, y and the series name from in a CSV file not written by tracktui
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ImportProfile {
+    pub name: String,
+    pub header: Vec<String>,
+    pub x: String,
+    pub y: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series: Option<String>, // Without it everything goes to a series named after the file
+}
+
+impl Config {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(toml::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn templates(&self) -> Vec<SeriesTemplate> {
+        let mut templates = SeriesTemplate::builtin();
+        templates.extend(self.templates.iter().cloned());
+        templates
+    }
+}
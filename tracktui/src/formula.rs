@@ -0,0 +1,338 @@
+use std::fmt;
+
+// Expression over other series, like 2.2 * weight.y or ("body fat".y / 100) * weight.y.
+// x is the x of the point being computed
+pub enum Expr {
+    Num(f64),
+    X,
+    Ref(String), // y of the named series
+    Neg(Box<Expr>),
+    Bin(char, Box<Expr>, Box<Expr>),
+    Call(String, Box<Expr>),
+}
+
+const FUNCTIONS: [&str; 5] = ["abs", "sqrt", "ln", "exp", "round"];
+
+#[derive(Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Name(String),
+    Quoted(String),
+    Op(char),
+}
+
+pub fn parse(s: &str) -> Result<Expr, String> {
+    let tokens = tokenize(s)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.expr()?;
+    match parser.tokens.get(parser.pos) {
+        None => Ok(expr),
+        Some(_) => Err(format!("Unexpected {}", parser.describe())),
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '+' | '-' | '*' | '/' | '^' | '(' | ')' | '.' => {
+                tokens.push(Token::Op(c));
+                i += 1;
+            }
+            '"' => {
+                let end = chars[i + 1..].iter().position(|&c| c == '"').ok_or("Unclosed quote")? + i + 1;
+                tokens.push(Token::Quoted(chars[i + 1..end].iter().collect()));
+                i = end + 1;
+            }
+            _ if c.is_ascii_digit() => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                tokens.push(Token::Num(text.parse().map_err(|_| format!("Not a number: {}", text))?));
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Name(chars[start..i].iter().collect()));
+            }
+            _ => return Err(format!("Unexpected {}", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, op: char) -> bool {
+        let found = self.tokens.get(self.pos) == Some(&Token::Op(op));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn describe(&self) -> String {
+        match self.tokens.get(self.pos) {
+            Some(Token::Num(n)) => n.to_string(),
+            Some(Token::Name(name) | Token::Quoted(name)) => name.clone(),
+            Some(Token::Op(op)) => op.to_string(),
+            None => "end".to_string(),
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut left = self.term()?;
+        loop {
+            let op = match () {
+                _ if self.eat('+') => '+',
+                _ if self.eat('-') => '-',
+                _ => return Ok(left),
+            };
+            left = Expr::Bin(op, Box::new(left), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut left = self.factor()?;
+        loop {
+            let op = match () {
+                _ if self.eat('*') => '*',
+                _ if self.eat('/') => '/',
+                _ => return Ok(left),
+            };
+            left = Expr::Bin(op, Box::new(left), Box::new(self.factor()?));
+        }
+    }
+
+    // ^ binds tighter than a leading minus on its right only, -2^2 is -4
+    fn factor(&mut self) -> Result<Expr, String> {
+        if self.eat('-') {
+            return Ok(Expr::Neg(Box::new(self.factor()?)));
+        }
+        let base = self.primary()?;
+        match self.eat('^') {
+            true => Ok(Expr::Bin('^', Box::new(base), Box::new(self.factor()?))),
+            false => Ok(base),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let at = self.describe();
+        match self.next() {
+            Some(Token::Num(n)) => Ok(Expr::Num(n)),
+            Some(Token::Op('(')) => {
+                let inner = self.expr()?;
+                match self.eat(')') {
+                    true => Ok(inner),
+                    false => Err(format!("Expected ) at {}", self.describe())),
+                }
+            }
+            Some(Token::Name(name)) if name == "x" && !self.tokens.get(self.pos).is_some_and(|t| *t == Token::Op('.')) => Ok(Expr::X),
+            Some(Token::Name(name)) if FUNCTIONS.contains(&name.as_str()) && self.eat('(') => {
+                let arg = self.expr()?;
+                match self.eat(')') {
+                    true => Ok(Expr::Call(name, Box::new(arg))),
+                    false => Err(format!("Expected ) at {}", self.describe())),
+                }
+            }
+            Some(Token::Name(name) | Token::Quoted(name)) => {
+                match self.eat('.') && self.next() == Some(Token::Name("y".to_string())) {
+                    true => Ok(Expr::Ref(name)),
+                    false => Err(format!("Write {}.y for the values of {}", name, name)),
+                }
+            }
+            _ => Err(format!("Unexpected {}", at)),
+        }
+    }
+}
+
+impl Expr {
+    // Series referenced, in order of first appearance
+    pub fn refs(&self) -> Vec<String> {
+        let mut refs = Vec::new();
+        self.collect_refs(&mut refs);
+        refs
+    }
+
+    fn collect_refs(&self, refs: &mut Vec<String>) {
+        match self {
+            Expr::Ref(name) if !refs.contains(name) => refs.push(name.clone()),
+            Expr::Neg(e) | Expr::Call(_, e) => e.collect_refs(refs),
+            Expr::Bin(_, a, b) => {
+                a.collect_refs(refs);
+                b.collect_refs(refs);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn rename(&mut self, old: &str, new: &str) {
+        match self {
+            Expr::Ref(name) if name == old => *name = new.to_string(),
+            Expr::Neg(e) | Expr::Call(_, e) => e.rename(old, new),
+            Expr::Bin(_, a, b) => {
+                a.rename(old, new);
+                b.rename(old, new);
+            }
+            _ => {}
+        }
+    }
+
+    // None where a referenced series has no value or the result is not a number
+    pub fn eval(&self, x: f64, y_of: &dyn Fn(&str) -> Option<f64>) -> Option<f64> {
+        let v = match self {
+            Expr::Num(n) => *n,
+            Expr::X => x,
+            Expr::Ref(name) => y_of(name)?,
+            Expr::Neg(e) => -e.eval(x, y_of)?,
+            Expr::Bin(op, a, b) => {
+                let (a, b) = (a.eval(x, y_of)?, b.eval(x, y_of)?);
+                match op {
+                    '+' => a + b,
+                    '-' => a - b,
+                    '*' => a * b,
+                    '/' => a / b,
+                    _ => a.powf(b),
+                }
+            }
+            Expr::Call(f, e) => {
+                let v = e.eval(x, y_of)?;
+                match f.as_str() {
+                    "abs" => v.abs(),
+                    "sqrt" => v.sqrt(),
+                    "ln" => v.ln(),
+                    "exp" => v.exp(),
+                    _ => v.round(),
+                }
+            }
+        };
+        v.is_finite().then_some(v)
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Bin('+' | '-', ..) => 1,
+            Expr::Bin('*' | '/', ..) => 2,
+            Expr::Neg(_) => 3,
+            Expr::Bin(..) => 4,
+            _ => 5,
+        }
+    }
+}
+
+// Back to text with only the parentheses needed, so renamed references can be stored
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let wrap = |f: &mut fmt::Formatter, e: &Expr, parens: bool| match parens {
+            true => write!(f, "({})", e),
+            false => write!(f, "{}", e),
+        };
+        match self {
+            Expr::Num(n) => write!(f, "{}", n),
+            Expr::X => write!(f, "x"),
+            Expr::Ref(name) if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+                && !name.starts_with(|c: char| c.is_ascii_digit()) => write!(f, "{}.y", name),
+            Expr::Ref(name) => write!(f, "\"{}\".y", name),
+            Expr::Neg(e) => {
+                write!(f, "-")?;
+                wrap(f, e, e.precedence() < 4)
+            }
+            Expr::Bin(op, a, b) => {
+                let p = self.precedence();
+                // a - (b - c) and 2 ^ 3 ^ 2 keep their grouping
+                wrap(f, a, a.precedence() < p || (*op == '^' && a.precedence() == p))?;
+                write!(f, " {} ", op)?;
+                wrap(f, b, b.precedence() < p || (b.precedence() == p && *op != '^' && matches!(op, '-' | '/')))
+            }
+            Expr::Call(name, e) => write!(f, "{}({})", name, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(s: &str) -> Option<f64> {
+        let y_of = |name: &str| match name {
+            "weight" => Some(80.0),
+            "body fat" => Some(20.0),
+            _ => None,
+        };
+        parse(s).unwrap().eval(3.0, &y_of)
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(eval("1 + 2 * 3"), Some(7.0));
+        assert_eq!(eval("(1 + 2) * 3"), Some(9.0));
+        assert_eq!(eval("10 - 4 - 3"), Some(3.0));
+        assert_eq!(eval("12 / 3 / 2"), Some(2.0));
+        assert_eq!(eval("2 ^ 3 ^ 2"), Some(512.0));
+        assert_eq!(eval("2 * x ^ 2"), Some(18.0));
+    }
+
+    #[test]
+    fn leading_minus() {
+        assert_eq!(eval("-2^2"), Some(-4.0));
+        assert_eq!(eval("(-2)^2"), Some(4.0));
+        assert_eq!(eval("2^-1"), Some(0.5));
+        assert_eq!(eval("--3"), Some(3.0));
+    }
+
+    #[test]
+    fn references() {
+        assert_eq!(eval("\"body fat\".y / 100 * weight.y"), Some(16.0));
+        assert_eq!(eval("missing.y + 1"), None);
+        assert_eq!(eval("sqrt(-1)"), None);
+        assert_eq!(parse("weight").err(), Some("Write weight.y for the values of weight".to_string()));
+        assert_eq!(parse("\"body fat.y").err(), Some("Unclosed quote".to_string()));
+        assert_eq!(parse("weight.y \"lean\".y").err(), Some("Unexpected lean".to_string()));
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for s in [
+            "1 + 2 * 3",
+            "(1 + 2) * 3",
+            "a.y - (b.y - c.y)",
+            "a.y / (b.y / 2)",
+            "2 ^ 3 ^ 2",
+            "(2 ^ 3) ^ 2",
+            "-2 ^ 2",
+            "(-2) ^ 2",
+            "-(x + 1)",
+            "abs(x - 1) * \"body fat\".y",
+            "\"2nd\".y + x",
+        ] {
+            assert_eq!(parse(s).unwrap().to_string(), s);
+        }
+    }
+
+    #[test]
+    fn rename() {
+        let mut expr = parse("weight.y * (1 - \"body fat\".y / 100) + weight.y").unwrap();
+        expr.rename("weight", "mass kg");
+        expr.rename("body fat", "fat");
+        assert_eq!(expr.to_string(), "\"mass kg\".y * (1 - fat.y / 100) + \"mass kg\".y");
+        assert_eq!(expr.refs(), ["mass kg", "fat"]);
+    }
+}
@@ -0,0 +1,23 @@
+use std::{
+    error::Error,
+    process::{Command, Stdio},
+    thread,
+};
+
+// Runs `command` with sh in the background, `args` are its $1, $2, ...
+// Output is dropped so it cannot draw over the TUI
+pub fn run(command: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .arg("tracktui")
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // Reap it once it is done
+    thread::spawn(move || child.wait());
+    Ok(())
+}
@@ -1,51 +1,196 @@
-use std::{error::Error, fs::File};
+mod analysis;
+mod config;
+mod formula;
+mod hooks;
+mod server;
+mod theme;
+
+use chrono::Datelike;
+use std::{
+    borrow::Cow,
+    error::Error,
+    fs::{self, File},
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::{atomic::{AtomicUsize, Ordering}, mpsc::{self, Receiver}},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
 use serde::{Serialize, Deserialize};
 
-use color_eyre::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use color_eyre::{eyre::eyre, Result};
+use config::{Config, ImportProfile, MarkerStyle, RollingKind, SeriesTemplate, SortOrder, ViewMode, YAxis, CONFIG_PATH, MAX_KPIS};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind};
+use server::ApiRequest;
+use theme::{Theme, THEMES_DIR};
 use ratatui::{
-    layout::{Flex, Rect, Constraint, Layout},
+    layout::{Flex, Rect, Constraint, Layout, Margin, Position},
     style::{Color, Style, Modifier, Stylize},
     symbols,
     text::{Span, Text, Line},
     prelude::{Alignment},
-    widgets::{Cell, Row, Padding, Clear, Axis, Block, Chart, Dataset, GraphType, Paragraph, Table, TableState},
-    DefaultTerminal, Frame,
+    widgets::{Bar, BarChart, BarGroup, Cell, Row, Padding, Clear, Axis, Block, Chart, Dataset, GraphType, Paragraph, Table, TableState, Wrap},
+    backend::TestBackend,
+    DefaultTerminal, Frame, Terminal,
 };
 
 fn main() -> Result<()> {
     color_eyre::install()?;
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "render") {
+        return render(&args[1..]);
+    }
+
+    let mut app = App::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--demo" => app.demo = true,
+            "--read-only" => app.read_only = true,
+            "--serve" => app.serve = Some(args.next().ok_or_else(|| eyre!("--serve needs an address like 127.0.0.1:8765"))?),
+            "--cmd" => app.startup_commands.push(args.next().ok_or_else(|| eyre!("--cmd needs a command like \":view table\""))?),
+            _ => return Err(eyre!("Unknown argument: {}", arg)),
+        }
+    }
+
     let mut terminal = ratatui::init();
-    let result = App::new().run(&mut terminal);
+    let result = app.run(&mut terminal);
     ratatui::restore();
     result
 }
 
-#[derive(Default)]#[allow(dead_code)]
-enum ViewMode {
+// `tracktui render`: prints the graph of a series as plain text and exits
+fn render(args: &[String]) -> Result<()> {
+    let mut app = App::new();
+    let (mut series, mut width, mut height) = (None, 100, 30);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--series" => series = Some(args.next().ok_or_else(|| eyre!("--series needs a name"))?),
+            // Smaller leaves no room for the axes, and 0 would not draw at all
+            "--width" => width = args.next().and_then(|w| w.parse().ok()).filter(|&w| w >= 10)
+                .ok_or_else(|| eyre!("--width needs a number of at least 10"))?,
+            "--height" => height = args.next().and_then(|h| h.parse().ok()).filter(|&h| h >= 3)
+                .ok_or_else(|| eyre!("--height needs a number of at least 3"))?,
+            "--demo" => app.demo = true,
+            _ => return Err(eyre!("Unknown argument: {}", arg)),
+        }
+    }
+
+    app.load(false);
+    // A graph of half the data would pass for the real one, so load problems stop it
+    if let Some(recovery) = &app.recovery {
+        let row = &recovery.rejected[0];
+        return Err(eyre!("{} rows of data.csv could not be read, line {}: {}", recovery.rejected.len(), row.line, row.error));
+    }
+    if app.status_msg.starts_with("Could not load") {
+        return Err(eyre!("{}", app.status_msg));
+    }
+    app.refresh_aggregates();
+    if let Some(name) = series {
+        app.selected_serie = app.data_series.iter()
+            .position(|s| s.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| eyre!("No series named {}", name))?;
+    }
+
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    terminal.draw(|frame| app.draw_graph(frame, frame.area()))?;
+
+    let buffer = terminal.backend().buffer();
+    for row in buffer.content.chunks(width as usize) {
+        let line: String = row.iter().map(|cell| cell.symbol()).collect();
+        println!("{}", line.trim_end());
+    }
+    Ok(())
+}
+
+// Span compared against the one before it in the compare view
+#[derive(Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Period {
     #[default]
-    Graph,
-    Table,
-    Menu,
-    Help,
+    Week,
+    Month,
+}
+
+// Rows of the settings view, the last ones apply to the selected series
+#[derive(Clone, Copy, PartialEq)]
+enum Setting {
+    RefreshRate,
+    TableOrder,
+    Precision,
+    NudgeStep,
+    TrendCheck,
+    Autosave,
+    DefaultView,
+    Marker,
+    Theme,
+    Animations,
+    Mouse,
+    Kpis,
+    Unit,
+    Decimals,
+    InputStep,
+    Description,
+    UnitScale,
+    Goal,
+    Axis,
+    Labels,
+    SeriesMarker,
+    Prefill,
 }
 
+const SETTINGS: [Setting; 22] = [
+    Setting::RefreshRate,
+    Setting::TableOrder,
+    Setting::Precision,
+    Setting::NudgeStep,
+    Setting::TrendCheck,
+    Setting::Autosave,
+    Setting::DefaultView,
+    Setting::Marker,
+    Setting::Theme,
+    Setting::Animations,
+    Setting::Mouse,
+    Setting::Kpis,
+    Setting::Unit,
+    Setting::Decimals,
+    Setting::InputStep,
+    Setting::Description,
+    Setting::UnitScale,
+    Setting::Goal,
+    Setting::Axis,
+    Setting::Labels,
+    Setting::SeriesMarker,
+    Setting::Prefill,
+];
+
 #[derive(Default)]
 enum InputMode {
     #[default]
     Normal,
     Insert,
+    Cursor, // Stepping through the points of the graph
+    Legend, // Picking lines to hide in the graph legend
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, Copy, PartialEq)]
 enum InputField {
     #[default]
     X,
     Y,
+    Value(usize), // Extra value column after y
+    Error,
 }
 
 #[derive(Default)]
 struct App {
+    config: Config,
+    // Why tracktui.toml did not load, saving is refused while set so the
+    // defaults in use do not replace what the user wrote
+    config_error: Option<String>,
+    theme: Theme,
     mode: ViewMode,
     data_series: Vec<DataSeries>,
     selected_serie: usize,
@@ -55,501 +200,7610 @@ struct App {
     input_field: InputField,
     input_x: String,
     input_y: String,
+    input_values: Vec<String>,
+    input_error: String,
     status_msg: String,
 
+    x_window: Option<(f64, f64)>, // Visible x range, None fits all points
+    y_window: Option<(f64, f64)>, // Visible y range once panned up or down, None fits the points
+    cursor: usize, // Point under the graph cursor
+    legend_idx: usize,
+    hidden: Vec<String>, // Legend entries left out of the graph, whichever series is selected
+    overlay: Vec<String>, // Series drawn along with the selected one
+    aggregate_inputs: u64, // Fingerprint of the series computed series were last computed from
+    undo: Vec<(usize, f64, f64, f64)>, // (series, x, nudged y, previous y) of nudged points
+
     // Table View
     table_state: TableState,
+    table_all: bool, // List the points of every series instead of the selected one
+    table_order: SortOrder,
     confirm_delete: bool,
-    confirm_idx: usize,
+    confirm_idx: usize, // Index into DELETE_CHOICES
+    point_stats: bool, // Popup about the selected point
+
+    // Help View
+    help_scroll: u16,
+
+    // Trash View
+    trash_idx: usize, // Row, the latest deletion is row 0
+    confirm_empty: bool, // Dialog before deleting the trashed points for good
+
+    // Compare View
+    compare_period: Period,
+    compare_histogram: bool, // Distribution of y in both spans instead of their lines
+
+    // Batch View, one row of cells per point in the order of input_fields
+    batch: Vec<Vec<String>>,
+    batch_cell: (usize, usize),
+    batch_invalid: Vec<usize>, // Rows that failed to parse on the last commit
+
+    // Series View
+    series_idx: usize,
+    collapsed: Vec<String>, // Groups whose series are hidden
+    tag_filter: Option<String>, // Tag a series needs to be listed
+    show_archived: bool,
+    confirm_clear: bool, // Dialog before clearing every point of the selected series
+    series_search: Option<String>, // Query of the / popup
+    search_idx: usize, // Among the matches of the query
+
+    // Settings View
+    settings_idx: usize,
+    settings_edit: Option<String>, // Text typed for the selected setting
+
+    // :doctor report, open while Some
+    doctor: Option<Vec<Finding>>,
+    doctor_idx: usize,
+
+    // Entry breaking a rule, waiting for confirmation
+    pending_insert: Option<PendingInsert>,
 
+    // Reload
+    merge_report: Option<MergeReport>,
+    duplicate: Option<(usize, usize)>, // Series sharing a name, resolved in a dialog
+    duplicate_idx: usize, // Index into DUPLICATE_CHOICES
+    recovery: Option<Recovery>,
+    import_job: Option<ImportJob>,
+    loading: Option<Loading>,
+    data_mtime: Option<SystemTime>, // data.csv as last read or written, see check_data_file
+    saved_points: Option<Vec<(String, u64)>>, // Points hash of each series in data.csv, None when unknown
+    saved_meta: Option<u64>, // Hash of series.toml as last read or written
+
+    // Templates
+    template_idx: Option<usize>,
+
+    browser: Option<FileBrowser>,
+
+    // JSON API, see --serve. It speaks plain HTTP, so the token is only safe
+    // on 127.0.0.1. Reach it from other devices through an SSH tunnel or a
+    // reverse proxy with TLS rather than binding 0.0.0.0
+    serve: Option<String>,
+
+    // Run in order once loaded, see --cmd
+    startup_commands: Vec<String>,
+    api: Option<Receiver<ApiRequest>>,
+
+    // Command line, open while Some
+    command: Option<String>,
+    edit_pending: bool, // Open the selected series in $EDITOR once the event is handled
+    suspend_pending: bool, // Ctrl+Z, stop once the event is handled
+
+    // Macros
+    recording: Option<Vec<KeyCode>>,
+    macro_keys: Vec<KeyCode>,
+
+    read_only: bool,
+    unicode: bool, // Braille and half blocks can be drawn, see unicode_locale
+
+    // Demo
+    demo: bool,
+    tour: Option<usize>,
+    tour_points: usize,
+
+    // Animations, see ease
+    graph_view: Option<(usize, [Window; 2])>, // Series and x and y windows last drawn
+    graph_shown: [[f64; 2]; 2], // Bounds last drawn
+    graph_anim: Option<([[f64; 2]; 2], Instant)>, // Bounds eased from
+    table_shown: f64, // Row offset last drawn
+    table_anim: Option<(f64, Instant)>, // Row offset eased from
+
+    // Where the graph was last drawn, for mouse clicks
+    graph_plot: Option<(Rect, [f64; 2], [f64; 2])>,
+
+    last_draw: Option<Instant>,
+    draw_time: Duration, // Recent cost of a redraw, smoothed
+    last_save: Option<Instant>,
     exit: bool,
 }
 
-#[derive(Default, Serialize, Deserialize)]
+// Points live in data.csv, everything else about a series in series.toml
+#[derive(Default, Clone, Serialize, Deserialize)]
 struct DataSeries {
     name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    group: String, // Section of the series list, empty for none
+    #[serde(skip)]
     data: Vec<(f64, f64)>,
+    #[serde(skip)]
+    point_meta: Vec<PointMeta>, // Aligned with data, a shorter vector means defaults for the rest
+    #[serde(skip)]
+    summary: std::cell::Cell<Option<Summary>>, // Over the points as they are, None once they changed
+    #[serde(skip)]
+    revision: Revision,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    unit: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    description: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    created_at: String, // YYYY-MM-DD, empty for series older than the field
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    columns: Vec<String>, // Value column names, the first one is y
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    fixed_unit: bool, // Label the y axis in unit even for large values
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    x_format: String, // Label for x, a date format like %b %d or text with {} for the number
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    precision: Option<u8>, // Decimals shown for the series, None uses the app's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    input_step: Option<f64>, // Entered values are rounded to multiples of it, None keeps them as typed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    goal: Option<f64>,
+    #[serde(default)]
+    y_axis: YAxis,
+    #[serde(default, skip_serializing_if = "LabelFormat::is_auto")]
+    label_format: LabelFormat, // Of the y axis labels
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    scatter: bool, // Points drawn on their own instead of joined by lines
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    marker: Option<MarkerStyle>, // None draws with the app's marker
+    #[serde(default, skip_serializing_if = "Prefill::is_empty")]
+    prefill: Prefill,
+    #[serde(default, skip_serializing_if = "Rules::is_empty")]
+    rules: Rules,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rate_goal: Option<RateGoal>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    color: Option<Color>, // None picks one from the theme's series colors by position
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    trash: Vec<JsonPoint>, // Deleted points, oldest first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cadence: Option<String>, // Expected time between points, day, week, month or a span of x
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    average: Option<String>, // Window of the moving average drawn over the series, a period like cadence
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    trend_line: bool, // Least squares line through the points in view
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    reference_lines: bool, // REFERENCE_LINES at the mean, min and max of y
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    archived: bool, // Left out of the series list and [ ] until shown with A
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    favorite: bool, // Pinned to the top of the series list, 1-9 select it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    aggregate: Option<Aggregate>, // Points computed from the rest of the group, never saved
 }
 
-fn center(area: Rect, horizontal: Constraint, vertical: Constraint) -> Rect {
-    let [area] = Layout::horizontal([horizontal])
-        .flex(Flex::Center)
-        .areas(area);
-    let [area] = Layout::vertical([vertical]).flex(Flex::Center).areas(area);
-    area
+// Row of the series list
+#[derive(Clone, PartialEq)]
+enum ListEntry {
+    Group(String),
+    Serie(usize),
 }
 
-impl DataSeries {
-    fn new() -> Self {
-        Self {
-            name: "Graph".to_string(),
-            ..Default::default()
-        }
+// Checks on entered points, breaking one asks for confirmation
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Rules {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    y_min: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    y_max: Option<f64>,
+    positive: bool,
+    increasing_x: bool, // New x must come after every point already there
+    strict_x: bool, // Refuse points breaking increasing_x instead of asking
+}
+
+impl Rules {
+    fn is_empty(&self) -> bool {
+        *self == Rules::default()
     }
 
-    fn get_bounds(&self) -> (f64, f64) {
-        if self.data.is_empty() {
-            return (1.0, 1.0)
+    fn describe(&self) -> String {
+        let mut rules = Vec::new();
+        if self.positive {
+            rules.push("y > 0".to_string());
         }
-
-        let mut x_max = f64::NEG_INFINITY;
-        let mut y_max = f64::NEG_INFINITY;
-        for &(x, y) in &self.data {
-            x_max = x_max.max(x);
-            y_max = y_max.max(y);
+        if let Some(min) = self.y_min {
+            rules.push(format!("y ≥ {}", min));
+        }
+        if let Some(max) = self.y_max {
+            rules.push(format!("y ≤ {}", max));
+        }
+        match (self.increasing_x, self.strict_x) {
+            (true, true) => rules.push("x increasing (strict)".to_string()),
+            (true, false) => rules.push("x increasing".to_string()),
+            _ => {}
+        }
+        match rules.is_empty() {
+            true => "none".to_string(),
+            false => rules.join(", "),
         }
-        (x_max, y_max)
     }
+}
 
-    fn get_labels(&self) -> (Vec<Span<'_>>, Vec<Span<'_>>) {
-        let mut x_labels = Vec::new();
-        let mut y_labels = Vec::new();
-        let (x_max, y_max) = self.get_bounds();
-        let n_labels = std::cmp::min(5, self.data.len());
+const RULE_USAGE: &str = "Usage: rule <positive|increasing [strict]|min <y|off>|max <y|off>|clear>";
 
-        if n_labels == 0 {
-            return (vec![], vec![]);
-        }
+// Points waiting for confirmation because they break the series' rules
+struct PendingInsert {
+    points: Vec<(f64, f64, PointMeta)>,
+    violations: Vec<String>,
+}
+
+// Issue :doctor found with a series, and what to do about it
+struct Finding {
+    serie: usize,
+    issue: String,
+    hint: String,
+}
+
+// Options of the table's delete dialog, None cancels
+const DELETE_CHOICES: [(&str, Option<DeleteScope>); 4] = [
+    ("Point", Some(DeleteScope::Point)),
+    ("Same x", Some(DeleteScope::SameX)),
+    ("Series", Some(DeleteScope::Series)),
+    ("Cancel", None),
+];
+
+// Ways to resolve two series with the same name, which data.csv cannot tell apart
+const DUPLICATE_CHOICES: [&str; 3] = ["Rename", "Merge", "Keep both"];
+
+const RECOVERY_CHOICES: [&str; 4] = ["Keep good rows", "Back up", "Start fresh", "Quit"];
+
+// Flat lines M draws across the chart, by legend name
+const REFERENCE_LINES: [&str; 3] = ["mean", "min", "max"];
+
+// Legend lines o adds over the points one at a time, before taking them all away
+const OVERLAY_LINES: [&str; 3] = ["average", "trend", "goal"];
+
+#[derive(Clone, Copy)]
+enum DeleteScope {
+    Point,
+    SameX, // Every point of the series at the selected x
+    Series,
+}
+
+// File picker for the path left out of :import, :export or :workspace
+struct FileBrowser {
+    action: BrowseAction,
+    dir: PathBuf,
+    entries: Vec<(String, bool)>, // Name and whether it is a directory, ".." first
+    filter: String,
+    idx: usize, // Among the entries matching the filter
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum BrowseAction {
+    Import,
+    Export,
+    WorkspaceSave,
+    WorkspaceLoad,
+}
 
-        for i in 0..=n_labels {
-            x_labels.push(Span::styled(format!("{:.2}", i as f64 / n_labels as f64 * x_max), Style::default().add_modifier(Modifier::BOLD)));
-            y_labels.push(Span::styled(format!("{:.2}", i as f64 / n_labels as f64 * y_max), Style::default().add_modifier(Modifier::BOLD)));
+impl BrowseAction {
+    fn label(&self) -> &'static str {
+        match self {
+            BrowseAction::Import => "Import",
+            BrowseAction::Export => "Export",
+            BrowseAction::WorkspaceSave => "Save workspace",
+            BrowseAction::WorkspaceLoad => "Load workspace",
         }
+    }
 
-        (x_labels, y_labels)
+    // Whether the picked file may not exist yet
+    fn saves(&self) -> bool {
+        matches!(self, BrowseAction::Export | BrowseAction::WorkspaceSave)
     }
 }
 
-impl App {
-    fn new() -> Self {
-        Self {
-            mode: ViewMode::Graph,
-            selected_serie: 0,
-            status_msg: format!("h: help"),
-            ..Default::default()
-        }
+impl FileBrowser {
+    fn open(action: BrowseAction, dir: PathBuf) -> std::io::Result<Self> {
+        let mut browser = Self { action, dir, entries: Vec::new(), filter: String::new(), idx: 0 };
+        browser.read_dir()?;
+        Ok(browser)
     }
-    
-    fn write_csv(&mut self, path: String) -> Result<(), Box<dyn Error>> {
-        let file = File::create(path)?;
-        let mut wtr = csv::Writer::from_writer(file);
-        
-        wtr.write_record(&["name", "x", "y"])?;
-        
-        // Flatten: write each data point as a separate row
-        for serie in &self.data_series {
-            for &(x, y) in &serie.data {
-                wtr.write_record(&[
-                    serie.name.as_str(),
-                    &x.to_string(),
-                    &y.to_string(),
-                ])?;
-            }
+
+    // Directories first, hidden entries only while the filter starts with a dot
+    fn read_dir(&mut self) -> std::io::Result<()> {
+        let mut entries: Vec<(String, bool)> = fs::read_dir(&self.dir)?
+            .flatten()
+            .map(|e| (e.file_name().to_string_lossy().to_string(), e.path().is_dir()))
+            .collect();
+        entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.to_lowercase().cmp(&b.0.to_lowercase())));
+        if self.dir.parent().is_some() {
+            entries.insert(0, ("..".to_string(), true));
         }
-        
-        wtr.flush()?;
+        self.entries = entries;
+        self.filter.clear();
+        self.idx = 0;
         Ok(())
     }
-    
-    fn read_csv(&mut self, path: String) -> Result<(), Box<dyn Error>> {
-        let file = File::open(path)?;
-        let mut rdr = csv::Reader::from_reader(file);
-        
-        use std::collections::HashMap;
-        let mut series_map: HashMap<String, Vec<(f64, f64)>> = HashMap::new();
-        
-        for result in rdr.records() {
-            let record = result?;
-            let name = record.get(0).ok_or("Missing name")?.to_string();
-            let x: f64 = record.get(1).ok_or("Missing x")?.parse()?;
-            let y: f64 = record.get(2).ok_or("Missing y")?.parse()?;
-            
-            series_map.entry(name).or_insert_with(Vec::new).push((x, y));
-        }
-        
-        // Convert HashMap to Vec<DataSeries>
-        for (name, mut data) in series_map {
-            data.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
-            self.data_series.push(DataSeries { name, data });
-        }
-        
-        Ok(())
+
+    fn visible(&self) -> Vec<&(String, bool)> {
+        let filter = self.filter.to_lowercase();
+        self.entries.iter()
+            .filter(|(name, _)| match filter.is_empty() {
+                true => name == ".." || !name.starts_with('.'),
+                false => name != ".." && name.to_lowercase().contains(&filter),
+            })
+            .collect()
     }
+}
 
-    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+// Target moving by `rate` every `per`, starting from a point of the series
+#[derive(Clone, Serialize, Deserialize)]
+struct RateGoal {
+    rate: f64,
+    per: String, // day, week, month or a span of x
+    start: (f64, f64),
+}
 
-        // Read csv
-        if let Err(e) = self.read_csv("data.csv".to_string()) {
-            self.status_msg = format!("Could not load data.csv: {}", e);
-            self.data_series.push(DataSeries::new());
-        }
+impl RateGoal {
+    fn target(&self, x: f64) -> Option<f64> {
+        parse_period(&self.per, self.start.0 >= DATE_MIN).map(|period| self.start.1 + self.rate / period * (x - self.start.0))
+    }
+}
 
-        // Add series if none
-        if self.data_series.is_empty() {
-            self.data_series.push(DataSeries::new());
-        }
-        
-        // Main loop
-        while !self.exit {
-            terminal.draw(|frame| self.draw(frame))?;
-            self.handle_events()?;
-        }
+// Combines the other series of a group, or the listed ones with their
+// weights, into one point per x or per period
+#[derive(Clone, Serialize, Deserialize)]
+struct Aggregate {
+    kind: AggregateKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    per: Option<Period>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    members: Vec<(String, f64)>, // Series name and weight, empty for the group
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    formula: String, // Expression over the members, see formula.rs
+}
 
-        // Write csv
-        if let Err(e) = self.write_csv("data.csv".to_string()) {
-            self.status_msg = format!("Could not write to data.csv (Press any ket to exit): {}", e);
-            terminal.draw(|frame| self.draw(frame))?;
-            event::read()?;
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum AggregateKind {
+    Sum,
+    Mean,
+    Min,
+    Max,
+    Last,
+    Count,
+    // Two members matched on x, see derive
+    Plus,
+    Minus,
+    Ratio,
+    Formula,
+}
+
+impl AggregateKind {
+    const ALL: [AggregateKind; 6] = [
+        AggregateKind::Sum, AggregateKind::Mean, AggregateKind::Min,
+        AggregateKind::Max, AggregateKind::Last, AggregateKind::Count,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            AggregateKind::Sum => "sum",
+            AggregateKind::Mean => "mean",
+            AggregateKind::Min => "min",
+            AggregateKind::Max => "max",
+            AggregateKind::Last => "last",
+            AggregateKind::Count => "count",
+            AggregateKind::Plus => "+",
+            AggregateKind::Minus => "-",
+            AggregateKind::Ratio => "/",
+            AggregateKind::Formula => "formula",
         }
+    }
 
-        Ok(())
+    fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.name() == s)
     }
+}
 
-    fn draw(&mut self, frame: &mut Frame) {
-        match self.mode {
-            ViewMode::Graph => self.draw_graph_view(frame),
-            ViewMode::Menu => self.draw_menu_view(frame),
-            ViewMode::Table => self.draw_table_view(frame),
-            ViewMode::Help => self.draw_help_view(frame),
+impl Aggregate {
+    fn label(&self) -> String {
+        let kind = self.kind.name();
+        match self.per {
+            Some(Period::Week) => format!("weekly {}", kind),
+            Some(Period::Month) => format!("monthly {}", kind),
+            None => kind.to_string(),
         }
     }
 
-    fn draw_menu_view(&self, frame: &mut Frame) {
-        let lines = vec![
-            Line::from(vec!["h".bold(), "   Help".into()]),
-            Line::from(vec!["g".bold(), "   Graph".into()]),
-            Line::from(vec!["t".bold(), "   Table".into()]),
-            Line::from(vec!["q".bold(), "   Quit".into()]),
-        ];
+    // Weighted sum, or weighted mean of the members present at each x. The
+    // other kinds take the weighted values of each x or period as they are
+    fn compute(&self, members: &[(&DataSeries, f64)]) -> Vec<(f64, f64)> {
+        if matches!(self.kind, AggregateKind::Plus | AggregateKind::Minus | AggregateKind::Ratio) {
+            return match members {
+                [(a, _), (b, _)] => self.derive(&a.data, &b.data),
+                _ => Vec::new(),
+            };
+        }
+        if self.kind == AggregateKind::Formula {
+            return self.evaluate(members);
+        }
 
-        let area = center(
-            frame.area(),
-            Constraint::Length(10),
-            Constraint::Length(lines.len() as u16),
-        );
+        // Bucket, x, y, weight
+        let mut points: Vec<(f64, f64, f64, f64)> = members.iter()
+            .flat_map(|&(serie, weight)| serie.data.iter().map(move |&(x, y)| (x, y, weight)))
+            .filter_map(|(x, y, weight)| match self.per {
+                Some(period) => period_bounds(x, period).map(|[_, start, _]| (start, x, y, weight)),
+                None => Some((x, x, y, weight)),
+            })
+            .collect();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
 
-        let text = Text::from(lines);
-        let menu = Paragraph::new(text).alignment(Alignment::Center);
-        frame.render_widget(Clear, area);
-        frame.render_widget(menu, area);
+        let mut out: Vec<(f64, f64)> = Vec::new();
+        for chunk in points.chunk_by(|a, b| a.0 == b.0) {
+            let terms: Vec<f64> = chunk.iter().map(|&(_, _, y, weight)| weight * y).collect();
+            let sum = round_like(terms.iter().sum(), &terms);
+            let weights: f64 = chunk.iter().map(|&(_, _, _, weight)| weight).sum();
+            let value = match self.kind {
+                AggregateKind::Sum => sum,
+                AggregateKind::Mean if weights != 0.0 => sum / weights,
+                AggregateKind::Mean => continue,
+                AggregateKind::Min => terms.iter().copied().fold(f64::INFINITY, f64::min),
+                AggregateKind::Max => terms.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                AggregateKind::Last => terms[terms.len() - 1],
+                AggregateKind::Count => chunk.len() as f64,
+                AggregateKind::Plus | AggregateKind::Minus | AggregateKind::Ratio | AggregateKind::Formula => {
+                    unreachable!("computed by derive and evaluate")
+                }
+            };
+            out.push((chunk[0].0, value));
+        }
+        out
     }
 
-    fn draw_help_view(&mut self, frame: &mut Frame) {
-        let lines = vec![
-            Line::from(vec!["h".bold(), "   Help".into()]),
-            Line::from(vec!["m".bold(), "   Menu".into()]),
-            Line::from(vec!["g".bold(), "   Graph".into()]),
-            Line::from(vec!["t".bold(), "   Table".into()]),
-            Line::from(vec!["q".bold(), "   Quit".into()]),
-            Line::from(""),
-            Line::from(vec!["ENTER".bold(), "   Confirm".into()]),
-            Line::from(vec!["ESC".bold(), "   Deselect".into()]),
-            Line::from(vec!["TAB".bold(), "   Cycle".into()]),
-            Line::from(""),
-            Line::from(vec!["⇆".bold(), "   Cycle l/r".into()]),
-            Line::from(vec!["⇅".bold(), "   Cycle u/d".into()]),
-            Line::from(""),
-            Line::from(""),
-            Line::from(vec!["Graph View".bold().underlined()]),
-            Line::from(""),
-            Line::from(vec!["i".bold(), "   Insert data".into()]),
-            Line::from(""),
-            Line::from(""),
-            Line::from(vec!["Table View".bold().underlined()]),
-            Line::from(""),
-            Line::from(vec!["d".bold(), "   Delete".into()]),
-        ];
+    // The formula at every x of the first series it refers to, the others
+    // interpolated there. Leaves out x where any of them has no value
+    fn evaluate(&self, members: &[(&DataSeries, f64)]) -> Vec<(f64, f64)> {
+        let Ok(expr) = formula::parse(&self.formula) else { return Vec::new() };
+        let Some((first, _)) = members.first() else { return Vec::new() };
+        first.data.iter()
+            .filter_map(|&(x, _)| {
+                let y_of = |name: &str| {
+                    let (serie, _) = members.iter().find(|(s, _)| s.name == name)?;
+                    interpolate(&serie.data, x)
+                };
+                Some((x, expr.eval(x, &y_of)?))
+            })
+            .collect()
+    }
 
-        let area = center(
-            frame.area(),
-            Constraint::Length(30),
-            Constraint::Length(lines.len() as u16 + 2),
-        );
+    // a and b at every x of either where both have points around it,
+    // interpolated linearly between the points of the other
+    fn derive(&self, a: &[(f64, f64)], b: &[(f64, f64)]) -> Vec<(f64, f64)> {
+        let (Some(a_first), Some(b_first), Some(a_last), Some(b_last)) = (a.first(), b.first(), a.last(), b.last()) else {
+            return Vec::new();
+        };
+        let (from, to) = (a_first.0.max(b_first.0), a_last.0.min(b_last.0));
+        let mut xs: Vec<f64> = a.iter().chain(b).map(|&(x, _)| x).filter(|&x| x >= from && x <= to).collect();
+        xs.sort_by(f64::total_cmp);
+        xs.dedup();
 
-        let text = Text::from(lines);
-        let help = Paragraph::new(text).alignment(Alignment::Center);
-        frame.render_widget(help, area);
+        xs.into_iter()
+            .filter_map(|x| {
+                let (ya, yb) = (interpolate(a, x)?, interpolate(b, x)?);
+                let y = match self.kind {
+                    AggregateKind::Plus => round_like(ya + yb, &[ya, yb]),
+                    AggregateKind::Minus => round_like(ya - yb, &[ya, yb]),
+                    _ if yb == 0.0 => return None,
+                    _ => ya / yb,
+                };
+                Some((x, y))
+            })
+            .collect()
     }
+}
 
-    fn draw_table_view(&mut self, frame: &mut Frame) {
-        let area = center(
-            frame.area(),
-            Constraint::Length(20),
-            Constraint::Percentage(50),
-        );
-
-        let chunks = Layout::vertical(vec![
-            Constraint::Min(5),
-            Constraint::Length(4),
-        ]).split(area);
+// y at x on the line through the points around it, data sorted by x
+fn interpolate(data: &[(f64, f64)], x: f64) -> Option<f64> {
+    let i = data.partition_point(|&(px, _)| px < x);
+    let &(x1, y1) = data.get(i)?;
+    if x1 == x {
+        return Some(y1);
+    }
+    let &(x0, y0) = data.get(i.checked_sub(1)?)?;
+    Some(y0 + (y1 - y0) * (x - x0) / (x1 - x0))
+}
 
-        self.draw_table(frame, chunks[0]);
+// Per-point data besides (x, y)
+#[derive(Default, Clone)]
+struct PointMeta {
+    error: Option<f64>,
+    values: Vec<f64>, // Extra value columns after y
+    pinned: bool, // Always labelled on the chart and listed in the stats panel
+}
 
-        match self.confirm_delete {
-            true => {
-                let text = Text::from(vec![
-                    Line::from(vec!["Delete?".bold()]),
-                    Line::from(vec![
-                        if self.confirm_idx == 0 { "Yes".bold() }
-                        else { "Yes".into() },
-                        "  ".into(),
-                        if self.confirm_idx == 1 { "No".bold() }
-                        else { "No".into() }
-                    ]),
-                ]);
-                let content = Paragraph::new(text).centered();
-                frame.render_widget(content, chunks[1]);
-            }
-            false => {
-                let content = Paragraph::new("h: help").centered();
-                frame.render_widget(content, chunks[1]);
-            }
-        }
-    }
+// Stamp of a series' points, new for every series and every change to its
+// points, so computed series can tell when to compute again
+#[derive(Clone, Copy, PartialEq, Hash)]
+struct Revision(usize);
 
-    fn draw_table(&mut self, frame: &mut Frame, area: Rect) {
-        let header = Row::new(vec!["X", "Y"])
-            .style(Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD))
-            .bottom_margin(1);
+static REVISIONS: AtomicUsize = AtomicUsize::new(0);
 
-        let rows: Vec<Row> = self.data_series[self.selected_serie].data
-            .iter()
-            .map(|&(x, y)| {
-                Row::new(vec![Cell::from(x.to_string()), Cell::from(y.to_string())])
-            })
-            .collect();
+impl Default for Revision {
+    fn default() -> Self {
+        Revision(REVISIONS.fetch_add(1, Ordering::Relaxed))
+    }
+}
 
-        let widths = [
-            Constraint::Percentage(50),
-            Constraint::Percentage(50),
-        ];
+// Extremes and sum of the points, so frames do not go over every point of a
+// series. Inserts fold into it, any other change starts it over
+#[derive(Clone, Copy)]
+struct Summary {
+    x_max: f64,
+    y_min: f64,
+    y_max: f64,
+    y_sum: f64,
+    low: f64, // Extremes taking in error bars and extra value columns
+    high: f64,
+    low_positive: f64, // Smallest of those above zero, for log scale
+}
 
-        let table = Table::new(rows, widths)
-            .header(header)
-            .block(Block::bordered()
-                .title("  Table ⇅ ")
-                .title_alignment(Alignment::Center)
-                .padding(Padding::uniform(2)))
-            .column_spacing(1)
-            .row_highlight_style(
-                Style::default()
-                .bg(Color::White)
-                .fg(Color::Black)
-                .add_modifier(Modifier::BOLD)
-            );
+impl Summary {
+    const EMPTY: Summary = Summary {
+        x_max: f64::NEG_INFINITY,
+        y_min: f64::INFINITY,
+        y_max: f64::NEG_INFINITY,
+        y_sum: 0.0,
+        low: f64::INFINITY,
+        high: f64::NEG_INFINITY,
+        low_positive: f64::INFINITY,
+    };
 
-        frame.render_stateful_widget(table, area, &mut self.table_state);
+    fn add(self, (x, y): (f64, f64), meta: Option<&PointMeta>) -> Summary {
+        let error = meta.and_then(|m| m.error).unwrap_or(0.0);
+        let values = meta.map_or(&[][..], |m| &m.values);
+        Summary {
+            x_max: self.x_max.max(x),
+            y_min: self.y_min.min(y),
+            y_max: self.y_max.max(y),
+            y_sum: self.y_sum + y,
+            low: values.iter().fold(self.low.min(y - error), |acc, &v| acc.min(v)),
+            high: values.iter().fold(self.high.max(y + error), |acc, &v| acc.max(v)),
+            low_positive: [y, y - error].iter().chain(values)
+                .filter(|&&v| v > 0.0)
+                .fold(self.low_positive, |acc, &v| acc.min(v)),
+        }
     }
+}
 
+// What the insert bar starts with instead of empty boxes
+#[derive(Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Prefill {
+    x: bool, // Latest x plus the step before it, the next day for dates
+    y: bool, // Values of the latest point
+}
 
-    fn draw_graph_view(&mut self, frame: &mut Frame) {
-        let chunks = Layout::vertical([
-            Constraint::Length(3), // Input
-            Constraint::Min(10), // Graph
-        ]).split(frame.area());
+impl Prefill {
+    const ALL: [Prefill; 4] = [
+        Prefill { x: false, y: false },
+        Prefill { x: true, y: false },
+        Prefill { x: false, y: true },
+        Prefill { x: true, y: true },
+    ];
 
-        // Input
-        self.draw_input_bar(frame, chunks[0]);
+    fn is_empty(&self) -> bool {
+        *self == Prefill::default()
+    }
 
-        // Graph
-        self.draw_graph(frame, chunks[1]);
+    fn label(&self) -> &'static str {
+        match (self.x, self.y) {
+            (false, false) => "off",
+            (true, false) => "next x",
+            (false, true) => "last y",
+            (true, true) => "next x, last y",
+        }
     }
+}
 
-    fn draw_input_bar(&mut self, frame: &mut Frame, area: Rect) {
-        let input_chunks = Layout::horizontal([
-            Constraint::Length(8), // X
-            Constraint::Length(8), // Y
-            Constraint::Min(20), // Status
+// How the y axis labels a tick
+#[derive(Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum LabelFormat {
+    #[default]
+    Auto, // As many decimals as the ticks need, in a larger unit for large values
+    Integer,
+    Si, // 1.5k, 20M
+    Currency, // Thousands separated, two decimals
+    Percent, // Fractions, 0.25 as 25%
+}
+
+impl LabelFormat {
+    const ALL: [LabelFormat; 5] = [LabelFormat::Auto, LabelFormat::Integer, LabelFormat::Si, LabelFormat::Currency, LabelFormat::Percent];
+
+    fn is_auto(&self) -> bool {
+        *self == LabelFormat::Auto
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            LabelFormat::Auto => "auto",
+            LabelFormat::Integer => "integer",
+            LabelFormat::Si => "SI suffix (1.5k)",
+            LabelFormat::Currency => "currency (1,234.50)",
+            LabelFormat::Percent => "percent (0.25 as 25%)",
+        }
+    }
+
+    // Tick t, `decimals` is what the step between ticks needs
+    fn format(&self, t: f64, decimals: usize) -> String {
+        match self {
+            LabelFormat::Auto => format!("{:.*}", decimals, t),
+            LabelFormat::Integer => format!("{:.0}", t),
+            LabelFormat::Si => {
+                let (scale, suffix) = [(1e12, "T"), (1e9, "G"), (1e6, "M"), (1e3, "k")].into_iter()
+                    .find(|&(scale, _)| t.abs() >= scale)
+                    .unwrap_or((1.0, ""));
+                format!("{}{}", format_value(t / scale, 2), suffix)
+            }
+            LabelFormat::Currency => {
+                let text = format!("{:.2}", t.abs());
+                let (whole, cents) = text.split_once('.').unwrap_or((&text, "00"));
+                let mut grouped = String::new();
+                for (i, digit) in whole.chars().enumerate() {
+                    if i > 0 && (whole.len() - i) % 3 == 0 {
+                        grouped.push(',');
+                    }
+                    grouped.push(digit);
+                }
+                format!("{}{}.{}", if t < 0.0 { "-" } else { "" }, grouped, cents)
+            }
+            LabelFormat::Percent => format!("{}%", format_value(t * 100.0, decimals.saturating_sub(2))),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SeriesFile {
+    series: Vec<DataSeries>,
+}
+
+// JSON export: each series with its metadata and points
+#[derive(Deserialize)]
+struct JsonFile {
+    series: Vec<JsonSeries>,
+}
+
+#[derive(Deserialize)]
+struct JsonSeries {
+    #[serde(flatten)]
+    serie: DataSeries,
+    points: Vec<JsonPoint>,
+}
+
+// Points of a series as they were at some time, kept in SNAPSHOTS_PATH
+#[derive(Default, Serialize, Deserialize)]
+struct SnapshotFile {
+    snapshots: Vec<Snapshot>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    series: String,
+    name: String,
+    taken: String, // Local time, YYYY-MM-DD HH:MM:SS
+    points: Vec<JsonPoint>,
+}
+
+const SNAPSHOTS_PATH: &str = "snapshots.json";
+
+// Files tracktui keeps its data in, in the current directory
+const OWN_FILES: [&str; 4] = ["data.csv", "series.toml", CONFIG_PATH, SNAPSHOTS_PATH];
+
+// Whether `path` is one of OWN_FILES, however it is written and whether or not it exists yet
+fn is_own_file(path: &Path) -> bool {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let same_dir = fs::canonicalize(dir).ok() == fs::canonicalize(".").ok();
+    same_dir && path.file_name().is_some_and(|name| OWN_FILES.iter().any(|file| name == *file))
+}
+
+// Point posted to the API
+#[derive(Deserialize)]
+struct ApiPoint {
+    x: Option<f64>,
+    y: f64,
+    #[serde(default)]
+    force: bool,
+}
+
+// How often the event loop checks for API requests while idle
+const API_POLL: Duration = Duration::from_millis(100);
+
+// Files are parsed on several threads in chunks of about this size
+const CHUNK_BYTES: usize = 1 << 20;
+
+// How often background loads and imports report how far they got
+const PROGRESS_POLL: Duration = Duration::from_millis(50);
+
+// Imports from this size on run in the background, with progress in the status bar.
+// A data.csv this large is read the same way on startup
+const LARGE_IMPORT: u64 = 8 << 20;
+
+// How often data.csv is checked for changes made by other programs
+const WATCH_POLL: Duration = Duration::from_secs(1);
+
+// Longest a frame is stretched to when redraws are slow
+const MAX_FRAME_TIME: Duration = Duration::from_millis(200);
+
+// Length of the graph and table animations, whatever the refresh rate
+const ANIMATION_TIME: Duration = Duration::from_millis(180);
+
+// Progress of an animation started at `start`, slowing down towards the end.
+// None once it is over
+fn ease(start: Instant) -> Option<f64> {
+    let t = start.elapsed().as_secs_f64() / ANIMATION_TIME.as_secs_f64();
+    (t < 1.0).then(|| 1.0 - (1.0 - t).powi(3))
+}
+
+// Workspace file: the JSON export plus the config and view state
+#[derive(Deserialize)]
+struct WorkspaceFile {
+    config: Config,
+    #[serde(default)]
+    ui: UiState,
+    series: Vec<JsonSeries>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct UiState {
+    selected: String, // Series name
+    mode: ViewMode,
+    x_window: Option<(f64, f64)>,
+    y_window: Option<(f64, f64)>,
+    table_all: bool,
+    table_order: SortOrder,
+    hidden: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct JsonPoint {
+    x: f64,
+    y: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    values: Vec<f64>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pinned: bool,
+}
+
+// Outcome of reconciling a reloaded data file with the series in memory
+#[derive(Default)]
+struct MergeReport {
+    source: String,
+    added: Vec<String>,
+    updated: Vec<(String, usize, usize)>, // name, points added, points removed
+    missing: Vec<String>,
+    pending: Option<Vec<DataSeries>>, // Series to merge once the report is confirmed
+}
+
+// Rows of data.csv that did not parse, shown on startup instead of dropping them silently
+struct Recovery {
+    rejected: Vec<RejectedRow>,
+    points: usize, // Read fine
+    choice: usize, // Index into RECOVERY_CHOICES
+    scroll: usize,
+    backup: Option<String>,
+    read_only: bool, // As before the dialog, saving is blocked while it is open
+}
+
+// Import parsed on a background thread, see LARGE_IMPORT
+struct ImportJob {
+    path: String,
+    rx: Receiver<ImportMsg>,
+}
+
+enum ImportMsg {
+    Progress(f64), // Share of the file parsed
+    Done(Result<Vec<DataSeries>, String>),
+}
+
+// data.csv parsed on a background thread at startup, the UI is up meanwhile
+// and locked so nothing is saved over the file before it is read
+struct Loading {
+    rx: Receiver<LoadMsg>,
+    progress: f64, // Share of the file parsed
+    read_only: bool, // As before, given back once the points are in
+}
+
+enum LoadMsg {
+    Progress(f64),
+    Done(Vec<DataSeries>, Vec<RejectedRow>),
+}
+
+// Series name, x, y and the rest of a row of data.csv
+type CsvRow = (String, f64, f64, PointMeta);
+
+// Visible range of a graph axis, None fits the points
+type Window = Option<(f64, f64)>;
+
+struct RejectedRow {
+    line: u64, // 0 when the file could not be read at all
+    text: String,
+    error: String,
+}
+
+impl MergeReport {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty()
+    }
+}
+
+const TOUR: [&str; 8] = [
+    "Welcome to tracktui! This demo loads generated series and nothing you do is saved. Press ENTER to start the tour.",
+    "This is the Graph view of the selected series. Press i to start inserting a point.",
+    "Type an X value, press TAB to switch to Y, type a Y value and press ENTER to insert it.",
+    "Your point is on the chart. Press t to open the Table view.",
+    "Every point of the series is listed here. Select a row with j/k or ⇅.",
+    "Press d to delete the selected row, pick Yes and confirm with ENTER.",
+    "Press f for the Spectrum view, the weekly cycle in the data shows up as a peak at period 7.",
+    "That's it! Press h in any view for the full key list. Press ENTER to end the tour.",
+];
+
+// Every key and command by where it works, drawn by the help view and
+// written as Markdown by :keys. Commands are listed with their arguments.
+// A key goes in the section of every view whose handle_*_input takes it
+const KEYMAP: [(&str, &[(&str, &str)]); 7] = [
+    ("Everywhere", &[
+        ("h", "Help"),
+        ("m", "Menu"),
+        ("g", "Graph"),
+        ("t", "Table"),
+        ("f", "Spectrum"),
+        ("c", "Compare"),
+        ("s", "Settings"),
+        ("S", "Series"),
+        ("1-9", "Favorite series, ALT+1-9 in the graph"),
+        (":", "Command"),
+        ("q", "Quit"),
+        ("ENTER", "Confirm"),
+        ("ESC", "Deselect"),
+        ("TAB", "Cycle"),
+        ("Q", "Record macro"),
+        ("@", "Replay macro"),
+        ("⇆", "Cycle l/r"),
+        ("⇅", "Cycle u/d"),
+    ]),
+    ("Commands", &[
+        ("goto <x|date>", "Select the nearest point"),
+        ("series <name>", "Select a series"),
+        ("view <name>", "Switch to a view, like table"),
+        ("rename <name>", "Rename the series"),
+        ("duplicate", "Copy the series"),
+        ("merge <series> [both|mean|first]", "Move another series' points into this one"),
+        ("export [file] [units]", "Write CSV, JSON or Markdown, units adds CSV columns"),
+        ("import [file] [x=<col> y=<col>]", "Read CSV or JSON"),
+        ("rate <n>/<period>", "Goal for the rate of change"),
+        ("edit", "Open the series in $EDITOR"),
+        ("rule <rule>", "Check entered points"),
+        ("cadence <period>|off", "Flag the series when overdue"),
+        ("average <period>|off", "Moving average over the graph"),
+        ("group [name]", "Move the series into a group"),
+        ("tags <tag>, ...", "Replace the series' tags"),
+        ("xformat [%b %d|#{}]", "Label x as a date or in a template"),
+        ("aggregate <fn> [week|month]", "Add a series computed from the group"),
+        ("rollup week|month <fn>", "Add the series per week or month"),
+        ("combine sum|mean <name> = <a>*<w>, ...", "Add a weighted sum or mean"),
+        ("derive <name> = <a> +|-|/ <b>", "Add a series computed from two"),
+        ("formula <name> = <expr>", "Add a series like 2.2 * weight.y"),
+        ("workspace save|load [file]", "Everything in one JSON file"),
+        ("doctor", "Check every series for gaps, stale or stuck data"),
+        ("move <dir>", "Move the data files, like into a synced folder"),
+        ("snapshot [name]", "Keep a copy of the series' points"),
+        ("snapshot list|diff|restore|delete <name>", "Look at or bring back a copy"),
+        ("theme [<name>|default]", "Use colors from themes/<name>.toml"),
+        ("theme export <name>", "Write the colors in use to themes/<name>.toml"),
+        ("keys [file]", "Write this list as Markdown"),
+    ]),
+    ("Graph and Table View", &[
+        ("R", "Reload, also in the menu"),
+        ("P", "Reload theme, also in the menu"),
+        ("L", "Lock / unlock, also in the menu"),
+        ("n", "New series, also in the series view"),
+        ("T", "New from template"),
+        ("[ ]", "Previous / next series"),
+    ]),
+    ("Graph View", &[
+        ("i", "Insert data"),
+        ("b", "Batch entry"),
+        ("v", "Crosshair with the point's values, ⇅ nudges y"),
+        ("l", "Legend, SPACE hides a line"),
+        ("click", "Fill in X and Y, with the Mouse setting"),
+        ("C", "Series color"),
+        ("p", "Points or lines"),
+        ("a", "Moving average, :average sets its window"),
+        ("r", "Trend line, slope in the status bar"),
+        ("M", "Mean, min and max lines"),
+        ("o", "Cycle lines: points, + average, + trend, + goal"),
+        ("=", "Fit all points"),
+        ("+/-", "Zoom in, out"),
+        ("⇆ ⇅", "Pan, also j/k"),
+        ("1-4", "Last 7d/30d/90d/1y, 0: all"),
+        ("TAB", "Next field"),
+    ]),
+    ("Table View", &[
+        ("r", "Rename series"),
+        ("d", "Delete"),
+        ("p", "Pin / unpin"),
+        ("i", "Point stats"),
+        ("x", "Trash"),
+        ("+/-", "Nudge y"),
+        ("u", "Undo nudge"),
+        ("a", "All series"),
+        ("o", "Sort order"),
+    ]),
+    ("Series View", &[
+        ("r", "Rename series"),
+        ("J/K", "Move series"),
+        ("ENTER", "Open series, fold group"),
+        ("C", "Series color"),
+        ("#", "Edit tags"),
+        ("F", "Filter by tag"),
+        ("p", "Pin to the top, 1-9 select"),
+        ("a", "Archive, unarchive"),
+        ("A", "Show archived series"),
+        ("/", "Find series by name"),
+        ("D", "Duplicate"),
+        ("o", "Overlay on the graph, O: clear"),
+        ("X", "Clear all points"),
+        ("d", "Delete"),
+    ]),
+    ("Trash View", &[
+        ("ENTER, r", "Restore point"),
+        ("E", "Empty trash"),
+    ]),
+];
+
+const KEYS_PATH: &str = "tracktui-keys.md";
+
+// Local date for created_at
+fn today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
+
+// Where the data files are, for messages
+fn current_dir_name() -> String {
+    std::env::current_dir().map_or_else(|_| ".".to_string(), |dir| dir.display().to_string())
+}
+
+// Whether the letters of query appear in order in name, ignoring case.
+// Higher scores for runs of adjacent letters and matches near the start
+fn fuzzy_score(query: &str, name: &str) -> Option<i32> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut last: Option<usize> = None;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let i = next + name[next..].iter().position(|&c| c == q)?;
+        score += match last {
+            Some(l) if l + 1 == i => 5,
+            _ if i == 0 || !name[i - 1].is_alphanumeric() => 3,
+            _ => 1,
+        };
+        last = Some(i);
+        next = i + 1;
+    }
+    Some(score * 100 - last.unwrap_or(0) as i32)
+}
+
+fn text_hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Parses an x value typed by the user, dates become UNIX timestamps (UTC midnight)
+fn parse_x(s: &str) -> Option<f64> {
+    if let Ok(x) = s.parse::<f64>() {
+        return Some(x);
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Some(date.and_hms_opt(0, 0, 0)?.and_utc().timestamp() as f64);
+    }
+    let time = ["%Y-%m-%d %H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S"].iter()
+        .find_map(|format| chrono::NaiveDateTime::parse_from_str(s, format).ok())?;
+    local_timestamp(time)
+}
+
+// Wall clock time in the local zone, see Config::timezone, as a UTC timestamp.
+// A time skipped when the clocks go forward is taken as an hour later, one
+// that happens twice when they go back as the first time
+fn local_timestamp(time: chrono::NaiveDateTime) -> Option<f64> {
+    use chrono::TimeZone;
+    let local = chrono::Local.from_local_datetime(&time).earliest()
+        .or_else(|| chrono::Local.from_local_datetime(&(time + chrono::Duration::hours(1))).earliest())?;
+    Some(local.timestamp() as f64)
+}
+
+// Timestamp formatted in the local zone. Midnight UTC is what a plain date
+// is stored as, it stands for the whole day and is formatted in UTC so the
+// day does not change with the zone
+fn format_date(x: f64, format: &str) -> Option<String> {
+    use std::fmt::Write;
+    let utc = chrono::DateTime::from_timestamp(x as i64, 0)?;
+    // An invalid format, like one from a hand-edited series.toml, gives None where to_string would panic
+    let mut text = String::new();
+    match x.rem_euclid(86400.0) == 0.0 {
+        true => write!(text, "{}", utc.format(format)),
+        false => write!(text, "{}", utc.with_timezone(&chrono::Local).format(format)),
+    }.ok()?;
+    Some(text)
+}
+
+// YYYY-MM-DD, with the local time of day unless x is a plain date
+fn describe_date(x: f64) -> Option<String> {
+    match x.rem_euclid(86400.0) == 0.0 {
+        true => format_date(x, "%Y-%m-%d"),
+        false => format_date(x, "%Y-%m-%d %H:%M"),
+    }
+}
+
+// A point from text cells laid out like `fields`. X may be a date, the
+// error is optional and must not be negative, every other cell is required
+fn parse_point(fields: &[InputField], cells: &[String]) -> Option<(f64, f64, PointMeta)> {
+    let (mut x, mut y, mut meta) = (None, None, PointMeta::default());
+    for (field, text) in fields.iter().zip(cells) {
+        match *field {
+            InputField::X => x = Some(parse_x(text)?),
+            InputField::Y => y = Some(text.parse().ok()?),
+            InputField::Value(_) => meta.values.push(text.parse().ok()?),
+            InputField::Error if text.is_empty() => {}
+            InputField::Error => meta.error = Some(text.parse().ok().filter(|&e: &f64| e >= 0.0)?),
+        }
+    }
+    Some((x?, y?, meta))
+}
+
+// Larger x values (from 1973 on) are taken to be UNIX timestamps
+const DATE_MIN: f64 = 1e8;
+
+// Starts of the period holding x, of the one before it and of the one after.
+// Dates are split by the calendar, plain x into spans of 7 or 30
+fn period_bounds(x: f64, period: Period) -> Option<[f64; 3]> {
+    if x < DATE_MIN {
+        let len = match period {
+            Period::Week => 7.0,
+            Period::Month => 30.0,
+        };
+        let start = (x / len).floor() * len;
+        return Some([start - len, start, start + len]);
+    }
+
+    let date = chrono::DateTime::from_timestamp(x as i64, 0)?.date_naive();
+    let (previous, start, next) = match period {
+        Period::Week => {
+            let start = date - chrono::Days::new(date.weekday().num_days_from_monday() as u64);
+            (start - chrono::Days::new(7), start, start + chrono::Days::new(7))
+        }
+        Period::Month => {
+            let start = date.with_day(1)?;
+            (start - chrono::Months::new(1), start, start + chrono::Months::new(1))
+        }
+    };
+    let timestamp = |d: chrono::NaiveDate| d.and_hms_opt(0, 0, 0).map(|t| t.and_utc().timestamp() as f64);
+    Some([timestamp(previous)?, timestamp(start)?, timestamp(next)?])
+}
+
+// Length of a period in x units. Dates count in seconds, plain x as days
+// like the rolling stats and window presets do
+fn parse_period(s: &str, dated: bool) -> Option<f64> {
+    let day = if dated { 86400.0 } else { 1.0 };
+    match s {
+        "day" => Some(day),
+        "week" => Some(7.0 * day),
+        "month" => Some(365.25 / 12.0 * day),
+        _ => s.parse().ok().filter(|&p: &f64| p > 0.0),
+    }
+}
+
+// Axis bounds snapped to round ticks, labelled with just enough decimals
+fn nice_axis(min: f64, max: f64, n_labels: usize) -> (Axis<'static>, [f64; 2]) {
+    nice_axis_with(min, max, n_labels, |t, decimals| format!("{:.*}", decimals, t))
+}
+
+// Axis over log10 of the values. Ticks sit on whole powers of ten once the
+// range spans one, a narrower range gets round exponents labelled with the value
+fn log_axis(min: f64, max: f64, n_labels: usize) -> (Axis<'static>, [f64; 2]) {
+    if max - min < 1.0 {
+        return nice_axis_with(min, max, n_labels, |t, _| log_label(t));
+    }
+    let (lo, hi) = (min.floor(), max.ceil());
+    let step = ((hi - lo) / n_labels.max(1) as f64).ceil();
+    let ticks: Vec<f64> = (0..).map(|i| lo + i as f64 * step).take_while(|&t| t < hi + step).collect();
+
+    let labels: Vec<Span> = ticks.iter()
+        .map(|&t| Span::styled(log_label(t), Style::default().add_modifier(Modifier::BOLD)))
+        .collect();
+    let bounds = [ticks[0], ticks[ticks.len() - 1]];
+    (Axis::default().bounds(bounds).labels(labels), bounds)
+}
+
+// 10^t to three significant digits
+fn log_label(t: f64) -> String {
+    let decimals = (2.0 - (t + 1e-9).floor()).max(0.0) as usize;
+    format_value(10f64.powf(t), decimals)
+}
+
+// Points as the chart plots them, with y as log10 on a log scale
+fn plot_points(points: &[(f64, f64)], log: bool) -> Cow<'_, [(f64, f64)]> {
+    match log {
+        true => points.iter().filter(|&&(_, y)| y > 0.0).map(|&(x, y)| (x, y.log10())).collect(),
+        false => Cow::Borrowed(points),
+    }
+}
+
+fn nice_axis_with(min: f64, max: f64, n_labels: usize, label: impl Fn(f64, usize) -> String) -> (Axis<'static>, [f64; 2]) {
+    let ticks = analysis::nice_ticks(min, max, n_labels);
+    let step = ticks[1] - ticks[0];
+    let decimals = (0..6).find(|&d| {
+        let scaled = step * 10f64.powi(d);
+        (scaled - scaled.round()).abs() < 1e-6
+    }).unwrap_or(6) as usize;
+
+    let labels: Vec<Span> = ticks.iter()
+        .map(|&t| Span::styled(label(t, decimals), Style::default().add_modifier(Modifier::BOLD)))
+        .collect();
+    let bounds = [ticks[0], ticks[ticks.len() - 1]];
+    (Axis::default().bounds(bounds).labels(labels), bounds)
+}
+
+// Larger unit to label an axis reaching up to max in, and how many of unit
+// make one of it. Units without known multiples get thousands, millions, ...
+fn unit_scale(unit: &str, max: f64) -> (String, f64) {
+    let steps: &[(&str, f64)] = match unit {
+        "ms" => &[("ms", 1.0), ("s", 1e3), ("min", 6e4), ("h", 3.6e6)],
+        "s" => &[("s", 1.0), ("min", 60.0), ("h", 3600.0)],
+        "B" => &[("B", 1.0), ("KB", 1e3), ("MB", 1e6), ("GB", 1e9), ("TB", 1e12)],
+        "g" => &[("g", 1.0), ("kg", 1e3)],
+        "m" => &[("m", 1.0), ("km", 1e3)],
+        _ => &[("", 1.0), ("thousand", 1e3), ("million", 1e6), ("billion", 1e9)],
+    };
+    let &(name, factor) = steps.iter().rev().find(|&&(_, f)| f <= max).unwrap_or(&steps[0]);
+    let name = match (steps[0].0, name, unit) {
+        ("", "", _) => unit.to_string(),
+        ("", name, "") => format!("{}s", name),
+        ("", name, unit) => format!("{} {}", name, unit),
+        _ => name.to_string(),
+    };
+    (name, factor)
+}
+
+// The option `step` places after current, wrapping around
+fn cycle<T: Copy + PartialEq>(options: &[T], current: T, step: isize) -> T {
+    let i = options.iter().position(|&o| o == current).unwrap_or(0) as isize + step;
+    options[i.rem_euclid(options.len() as isize) as usize]
+}
+
+// Rounded to at most `precision` decimals, without trailing zeros
+fn format_value(v: f64, precision: usize) -> String {
+    let s = format!("{:.*}", precision, v);
+    match s.contains('.') {
+        true => s.trim_end_matches('0').trim_end_matches('.').to_string(),
+        false => s,
+    }
+}
+
+// Decimals in the shortest text that reads back as v, which is what was
+// typed for entered values since that is how they are written to data.csv
+fn decimals(v: f64) -> usize {
+    let s = v.to_string();
+    match s.contains('e') {
+        true => 0,
+        false => s.split_once('.').map_or(0, |(_, d)| d.len()),
+    }
+}
+
+// Rounds the result of adding up `inputs` to their decimals, so sums like
+// 0.7 + 0.1 are stored as 0.8 and not 0.7999999999999999. Rounded through
+// the decimal text, which unlike scaling by 10^d is exact for any decimals
+fn round_like(v: f64, inputs: &[f64]) -> f64 {
+    let d = inputs.iter().map(|&i| decimals(i)).max().unwrap_or(0);
+    format!("{:.*}", d, v).parse().unwrap_or(v)
+}
+
+// Index of the point closest to x in data sorted by x, data must not be empty
+fn nearest_index(data: &[(f64, f64)], x: f64) -> usize {
+    match data.partition_point(|p| p.0 < x) {
+        0 => 0,
+        i if i == data.len() => i - 1,
+        i if x - data[i - 1].0 <= data[i].0 - x => i - 1,
+        i => i,
+    }
+}
+
+// Terminals without a UTF-8 locale tend to lack braille and half block glyphs.
+// Windows has no locale variables and its terminals handle both
+fn unicode_locale() -> bool {
+    cfg!(windows) || ["LC_ALL", "LC_CTYPE", "LANG"].iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .is_some_and(|value| {
+            let value = value.to_ascii_lowercase();
+            value.contains("utf-8") || value.contains("utf8")
+        })
+}
+
+// Mouse reporting has to be switched off again before the terminal is handed back
+fn set_mouse_capture(on: bool) {
+    let _ = match on {
+        true => crossterm::execute!(std::io::stdout(), crossterm::event::EnableMouseCapture),
+        false => crossterm::execute!(std::io::stdout(), crossterm::event::DisableMouseCapture),
+    };
+}
+
+// Plot area of a chart rendered into area, found from the corner of its axes
+fn plot_area(frame: &mut Frame, area: Rect) -> Option<Rect> {
+    let inner = Block::bordered().inner(area);
+    let buf = frame.buffer_mut();
+    let corner = inner.positions().find(|&p| buf[p].symbol() == symbols::line::BOTTOM_LEFT)?;
+    let plot = Rect::new(corner.x + 1, inner.y, inner.right().saturating_sub(corner.x + 1), corner.y - inner.y);
+    (plot.width >= 2 && plot.height >= 2).then_some(plot)
+}
+
+// Takes the terminal back after ratatui::restore handed it to another program
+fn resume_terminal(terminal: &mut DefaultTerminal) -> Result<()> {
+    crossterm::terminal::enable_raw_mode()?;
+    crossterm::execute!(std::io::stdout(), crossterm::terminal::EnterAlternateScreen)?;
+    terminal.clear()?;
+    Ok(())
+}
+
+fn center(area: Rect, horizontal: Constraint, vertical: Constraint) -> Rect {
+    let [area] = Layout::horizontal([horizontal])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([vertical]).flex(Flex::Center).areas(area);
+    area
+}
+
+impl DataSeries {
+    fn new() -> Self {
+        Self {
+            name: "Graph".to_string(),
+            created_at: today(),
+            ..Default::default()
+        }
+    }
+
+    // Generated example series for --demo
+    fn demo() -> Vec<Self> {
+        // Deterministic noise in [-1, 1]
+        let noise = |x: f64| ((x * 12.9898).sin() * 43758.5453).fract();
+        let week = |x: f64| (2.0 * std::f64::consts::PI * x / 7.0).sin();
+
+        let weight = (0..90)
+            .map(|d| d as f64)
+            .map(|x| (x, 84.0 - 0.04 * x + 0.5 * week(x) + 0.3 * noise(x)))
+            .collect();
+        let steps = (0..90)
+            .map(|d| d as f64)
+            .map(|x| (x, 8000.0 + 3000.0 * week(x) + 1200.0 * noise(x + 0.5)))
+            .collect();
+        let sleep = (0..90)
+            .map(|d| d as f64)
+            .map(|x| (x, 7.2 + 0.6 * (x / 11.0).sin() + 0.4 * noise(x + 0.25)))
+            .collect();
+
+        vec![
+            Self { name: "Weight".to_string(), data: weight, unit: "kg".to_string(), y_axis: YAxis::Fit, ..Default::default() },
+            Self { name: "Steps".to_string(), data: steps, unit: "steps".to_string(), goal: Some(10000.0), ..Default::default() },
+            Self { name: "Sleep".to_string(), data: sleep, unit: "h".to_string(), y_axis: YAxis::Fit, ..Default::default() },
+        ]
+    }
+
+    fn from_template(template: &SeriesTemplate) -> Self {
+        Self {
+            name: template.name.clone(),
+            unit: template.unit.clone(),
+            columns: template.columns.clone(),
+            goal: template.goal,
+            y_axis: template.y_axis,
+            created_at: today(),
+            ..Default::default()
+        }
+    }
+
+    fn error(&self, i: usize) -> Option<f64> {
+        self.point_meta.get(i).and_then(|m| m.error)
+    }
+
+    fn values(&self, i: usize) -> &[f64] {
+        self.point_meta.get(i).map_or(&[], |m| &m.values)
+    }
+
+    // What the points would break of the series' rules, one line per problem
+    // Violations no confirmation lets through
+    fn rejections(&self, points: &[(f64, f64, PointMeta)]) -> Vec<String> {
+        match self.rules.strict_x {
+            true => self.backdated(points),
+            false => Vec::new(),
+        }
+    }
+
+    fn violations(&self, points: &[(f64, f64, PointMeta)]) -> Vec<String> {
+        let rules = &self.rules;
+        let mut violations = Vec::new();
+
+        for &(_, y, _) in points {
+            if rules.positive && y <= 0.0 {
+                violations.push(format!("y = {} is not positive", y));
+            }
+            if let Some(min) = rules.y_min.filter(|&min| y < min) {
+                violations.push(format!("y = {} is below {}", y, min));
+            }
+            if let Some(max) = rules.y_max.filter(|&max| y > max) {
+                violations.push(format!("y = {} is above {}", y, max));
+            }
+        }
+        if rules.increasing_x {
+            violations.extend(self.backdated(points));
+        }
+        violations
+    }
+
+    // Points not after every point before them, in the series or earlier in `points`
+    fn backdated(&self, points: &[(f64, f64, PointMeta)]) -> Vec<String> {
+        let mut last_x = self.data.last().map(|&(x, _)| x);
+        let mut backdated = Vec::new();
+        for &(x, _, _) in points {
+            if let Some(last) = last_x.filter(|&last| x <= last) {
+                backdated.push(format!("x = {} is not after {}", x, last));
+            }
+            last_x = Some(last_x.map_or(x, |last| last.max(x)));
+        }
+        backdated
+    }
+
+    // Points whose y is far from what the recent points predict
+    fn off_trend(&self, points: &[(f64, f64, PointMeta)]) -> Vec<String> {
+        points.iter()
+            .filter_map(|&(x, y, _)| {
+                let (lo, hi) = analysis::expected_range(&self.data, x)?;
+                (y < lo || y > hi).then(|| format!(
+                    "y = {} is off trend, expected {} to {}",
+                    y, format_value(lo, 2), format_value(hi, 2)
+                ))
+            })
+            .collect()
+    }
+
+    // Slope over the last 10 points
+    fn trend(&self) -> Option<f64> {
+        let recent = &self.data[self.data.len().saturating_sub(10)..];
+        (recent.len() >= 2).then(|| analysis::linear_fit(recent).0)
+    }
+
+    // X the recent trend reaches the goal at, None without a goal, once it
+    // is reached or while the trend leads away from it
+    fn goal_eta(&self) -> Option<f64> {
+        let (goal, &(x, y), slope) = (self.goal?, self.data.last()?, self.trend()?);
+        let eta = x + (goal - y) / slope;
+        (eta.is_finite() && eta > x).then_some(eta)
+    }
+
+    // Whether the latest point is on the goal or past it, seen from the first point
+    fn goal_reached(&self) -> bool {
+        match (self.goal, self.data.first(), self.data.last()) {
+            (Some(goal), Some(&(_, first)), Some(&(_, last))) => (first - goal) * (last - goal) <= 0.0,
+            _ => false,
+        }
+    }
+
+    // Positive when the goal is above the latest point, or the rate goal rises
+    fn goal_direction(&self) -> Option<f64> {
+        match (self.goal, self.data.last()) {
+            (Some(goal), Some(&(_, y))) => Some(goal - y),
+            _ => self.rate_goal.as_ref().map(|goal| goal.rate),
+        }
+    }
+
+    // Arrow of the recent trend, empty with too few points, colored by
+    // whether it heads for the goal, away from it, or neither
+    fn trend_arrow(&self, theme: &Theme) -> (&'static str, Color) {
+        let arrow = match self.trend() {
+            Some(slope) if slope > 0.0 => " ↗",
+            Some(slope) if slope < 0.0 => " ↘",
+            Some(_) => " →",
+            None => "",
+        };
+        let color = match self.trend().zip(self.goal_direction()).map(|(a, b)| a * b) {
+            Some(p) if p > 0.0 => theme.on_track,
+            Some(p) if p < 0.0 => theme.off_track,
+            _ => theme.muted,
+        };
+        (arrow, color)
+    }
+
+    // Whether the latest point is older than the cadence. Only dated series
+    // can fall behind, plain x values have no now to compare with
+    fn overdue(&self) -> bool {
+        let Some(period) = self.cadence.as_deref().and_then(|c| parse_period(c, true)) else { return false };
+        match self.data.last() {
+            Some(&(x, _)) if x >= DATE_MIN => chrono::Utc::now().timestamp() as f64 - x > period,
+            Some(_) => false,
+            None => true,
+        }
+    }
+
+    // Problems worth a look, as (issue, hint). `unsorted` is whether the
+    // series' rows are out of order in data.csv, which loading hides
+    fn health(&self, now: f64, unsorted: bool, precision: usize) -> Vec<(String, String)> {
+        let Some(&(last, y)) = self.data.last() else {
+            return vec![("No points yet".to_string(), "Add some with i, or delete it with d in the series list".to_string())];
+        };
+        let dated = last >= DATE_MIN;
+        let span = |d: f64| match (dated, (d / 86400.0).round()) {
+            (true, 1.0) => "1 day".to_string(),
+            (true, days) => format!("{} days", days),
+            (false, _) => format_value(d, precision),
+        };
+        let at = |x: f64| self.describe_x(x, precision);
+        let mut found = Vec::new();
+
+        let expected = self.cadence.as_deref().and_then(|c| parse_period(c, dated)).or_else(|| analysis::median_step(&self.data));
+        if let Some(step) = expected && self.data.len() >= 3 {
+            let (gap, i) = self.data.windows(2).enumerate()
+                .map(|(i, w)| (w[1].0 - w[0].0, i))
+                .fold((0.0, 0), |a, b| if b.0 > a.0 { b } else { a });
+            if gap > 3.0 * step {
+                found.push((
+                    format!("Gap of {} after {} (usually {})", span(gap), at(self.data[i].0), span(step)),
+                    "Fill in the missing points if they are known".to_string(),
+                ));
+            }
+            let stale_after = self.cadence.as_deref().and_then(|c| parse_period(c, dated)).unwrap_or(3.0 * step);
+            if dated && now - last > stale_after {
+                found.push((
+                    format!("No new points for {}", span(now - last)),
+                    "Archive it with a in the series list if it is no longer tracked".to_string(),
+                ));
+            }
+        }
+
+        let repeated = self.data.iter().rev().take_while(|&&(_, py)| py == y).count();
+        if repeated >= 5 {
+            found.push((
+                format!("The last {} values are all {}", repeated, format_value(y, precision)),
+                "Check whatever feeds it, it may be stuck".to_string(),
+            ));
+        }
+
+        // Counted once per x however many points share it
+        let shared = self.data.windows(3)
+            .filter(|w| w[1].0 == w[2].0 && w[0].0 != w[1].0)
+            .count() + usize::from(self.data.len() >= 2 && self.data[0].0 == self.data[1].0);
+        if shared > 0 {
+            found.push((
+                match shared {
+                    1 => "Two or more points share an x".to_string(),
+                    n => format!("Points share an x at {} places", n),
+                },
+                "Delete the extra ones in the table, d then Same x".to_string(),
+            ));
+        }
+
+        if unsorted {
+            found.push((
+                "Rows are out of order in data.csv".to_string(),
+                "Nothing to fix by hand, saving writes them in order".to_string(),
+            ));
+        }
+        found
+    }
+
+    // X after the latest one, as a date for dated series
+    fn next_x(&self) -> Option<String> {
+        let &(last, _) = self.data.last()?;
+        let step = match self.data.len() {
+            1 if last >= DATE_MIN => 86400.0,
+            1 => 1.0,
+            n => last - self.data[n - 2].0,
+        };
+        if step <= 0.0 {
+            return None;
+        }
+        let next = round_like(last + step, &[last, step]);
+        match next >= DATE_MIN {
+            true => describe_date(next),
+            false => Some(next.to_string()),
+        }
+    }
+
+    fn pinned(&self, i: usize) -> bool {
+        self.point_meta.get(i).is_some_and(|m| m.pinned)
+    }
+
+    fn toggle_pin(&mut self, i: usize) {
+        self.point_meta.resize(self.data.len(), PointMeta::default());
+        self.point_meta[i].pinned = !self.point_meta[i].pinned;
+    }
+
+    // Inserts after any points with the same x, keeping data sorted
+    fn insert_point(&mut self, x: f64, y: f64, meta: PointMeta) {
+        let i = self.data.partition_point(|p| p.0 <= x);
+        self.point_meta.resize(self.data.len(), PointMeta::default());
+        self.summary.set(self.summary.get().map(|summary| summary.add((x, y), Some(&meta))));
+        self.revision = Revision::default();
+        self.data.insert(i, (x, y));
+        self.point_meta.insert(i, meta);
+    }
+
+    // Anything changing data or point_meta besides insert_point calls this
+    fn points_changed(&mut self) {
+        self.summary.set(None);
+        self.revision = Revision::default();
+    }
+
+    // Points within [x0, x1] and the one either side, so lines still run off the
+    // edges of a zoomed chart without handing it every point
+    fn visible(&self, [x0, x1]: [f64; 2]) -> &[(f64, f64)] {
+        let start = self.data.partition_point(|p| p.0 < x0).saturating_sub(1);
+        let end = (self.data.partition_point(|p| p.0 <= x1) + 1).min(self.data.len());
+        &self.data[start..end.max(start)]
+    }
+
+    fn summary(&self) -> Summary {
+        if let Some(summary) = self.summary.get() {
+            return summary;
+        }
+        let summary = self.data.iter().enumerate()
+            .fold(Summary::EMPTY, |acc, (i, &p)| acc.add(p, self.point_meta.get(i)));
+        self.summary.set(Some(summary));
+        summary
+    }
+
+    // Removes a point, keeping it in the trash to restore later
+    fn trash_point(&mut self, i: usize) {
+        let (x, y) = self.data[i];
+        self.trash.push(JsonPoint { x, y, error: self.error(i), values: self.values(i).to_vec(), pinned: self.pinned(i) });
+        self.remove_point(i);
+    }
+
+    // Empties the series for a new tracking period, the points stay in the trash
+    fn clear_points(&mut self) -> usize {
+        let n = self.data.len();
+        for i in (0..n).rev() {
+            self.trash_point(i);
+        }
+        n
+    }
+
+    fn restore_point(&mut self, t: usize) {
+        let p = self.trash.remove(t);
+        self.insert_point(p.x, p.y, PointMeta { error: p.error, values: p.values, pinned: p.pinned });
+    }
+
+    fn remove_point(&mut self, i: usize) {
+        self.points_changed();
+        self.data.remove(i);
+        if i < self.point_meta.len() {
+            self.point_meta.remove(i);
+        }
+    }
+
+    fn has_errors(&self) -> bool {
+        self.point_meta.iter().any(|m| m.error.is_some())
+    }
+
+    fn y_name(&self) -> &str {
+        self.columns.first().map_or("Y", String::as_str)
+    }
+
+    fn extra_columns(&self) -> &[String] {
+        self.columns.get(1..).unwrap_or(&[])
+    }
+
+    // Window of the moving average until one is set, a week for dates and 7 x otherwise
+    fn default_average(&self) -> &'static str {
+        match self.data.first().is_some_and(|&(x, _)| x >= DATE_MIN) {
+            true => "week",
+            false => "7",
+        }
+    }
+
+    // Y of each of REFERENCE_LINES, empty without points
+    fn reference_values(&self) -> Vec<f64> {
+        if self.data.is_empty() {
+            return Vec::new();
+        }
+        let summary = self.summary();
+        vec![summary.y_sum / self.data.len() as f64, summary.y_min, summary.y_max]
+    }
+
+    // Slope and intercept of the least squares line through the points in
+    // the x window, None without two distinct x
+    fn trend_fit(&self, window: Window) -> Option<(f64, f64)> {
+        let points = match window {
+            Some((x0, x1)) => &self.data[self.data.partition_point(|p| p.0 < x0)..self.data.partition_point(|p| p.0 <= x1)],
+            None => &self.data[..],
+        };
+        match (points.first(), points.last()) {
+            (Some(first), Some(last)) if first.0 < last.0 => Some(analysis::linear_fit(points)),
+            _ => None,
+        }
+    }
+
+    fn precision(&self, default: usize) -> usize {
+        self.precision.map_or(default, usize::from)
+    }
+
+    // Nearest multiple of the input step, with no more decimals than the step
+    fn snap(&self, v: f64) -> f64 {
+        match self.input_step {
+            Some(step) => round_like((v / step).round() * step, &[step]),
+            None => v,
+        }
+    }
+
+    // X in messages, dates as YYYY-MM-DD unless the series has its own format
+    fn describe_x(&self, x: f64, precision: usize) -> String {
+        match (x >= DATE_MIN, self.x_format.is_empty()) {
+            (true, true) => describe_date(x).unwrap_or_else(|| format_value(x, precision)),
+            _ => self.format_x(x, precision),
+        }
+    }
+
+    // X as labelled on the chart and in the table. Date formats only apply
+    // to dates, plain x falls back to the number
+    fn format_x(&self, x: f64, precision: usize) -> String {
+        match self.x_format.as_str() {
+            "" => format_value(x, precision),
+            template if template.contains("{}") => template.replace("{}", &format_value(x, precision)),
+            // A format chrono cannot write falls back to the plain date
+            format if x >= DATE_MIN => format_date(x, format).or_else(|| describe_date(x)).unwrap_or_else(|| format_value(x, precision)),
+            _ => format_value(x, precision),
+        }
+    }
+
+    // X range of the points, and their y range taking in error bars and extra columns
+    fn get_bounds(&self) -> [(f64, f64); 2] {
+        if self.data.is_empty() {
+            return [(1.0, 1.0), (1.0, 1.0)]
+        }
+        let summary = self.summary();
+        [(self.data[0].0, summary.x_max), (summary.low, summary.high)]
+    }
+
+    // Dates from the first point, plain x from 0 unless points lie below it
+    fn get_x_range(&self) -> (f64, f64) {
+        let [(x_min, x_max), _] = self.get_bounds();
+        match x_min >= DATE_MIN {
+            true => (x_min, x_max),
+            false => (x_min.min(0.0), x_max),
+        }
+    }
+
+    // Takes in the goal, so its line is always on the chart. Overlaid
+    // series are ranged by the axis of the series they are drawn over
+    fn get_y_range(&self, axis: YAxis) -> (f64, f64) {
+        let [_, (low, high)] = self.get_bounds();
+        let y_max = match (self.goal, self.data.is_empty()) {
+            (Some(goal), false) => high.max(goal),
+            _ => high,
+        };
+        match axis {
+            // Zero stays on the chart whether the points are above or below it
+            YAxis::Zero => (0.0_f64.min(self.goal.unwrap_or(0.0)).min(low), y_max.max(0.0)),
+            YAxis::Fit => {
+                let y_min = y_max.min(self.goal.unwrap_or(y_max)).min(low);
+                let pad = if y_max > y_min { (y_max - y_min) * 0.1 } else { 1.0 };
+                (y_min - pad, y_max + pad)
+            }
+            YAxis::Log => {
+                let goal = self.goal.filter(|&g| g > 0.0);
+                let bottom = goal.map_or(self.summary().low_positive, |g| g.min(self.summary().low_positive));
+                match bottom.is_finite() {
+                    true => (bottom.log10(), y_max.max(bottom).log10()),
+                    false => (0.0, 1.0),
+                }
+            }
+        }
+    }
+
+    // X and y axes with their bounds, widened to round tick values
+    fn get_axes(&self, (x_min, x_max): (f64, f64), (y_min, y_max): (f64, f64), area: Rect) -> [(Axis<'static>, [f64; 2]); 2] {
+        if self.data.is_empty() {
+            return [
+                (Axis::default().bounds([x_min, x_max]), [x_min, x_max]),
+                (Axis::default().bounds([y_min, y_max]), [y_min, y_max]),
+            ];
+        }
+
+        // As many labels as fit the chart with room between them, the widest
+        // x label is taken to be about as wide as the one for x_max
+        let x_width = match self.x_format.is_empty() {
+            true => format_value(x_max, 2).len(),
+            false => self.format_x(x_max, 2).chars().count(),
+        };
+        let n_x = (area.width as usize / (x_width + 6)).clamp(2, 10).min(self.data.len());
+        let n_y = (area.height as usize / 5).clamp(2, 10).min(self.data.len());
+
+        let x_axis = match self.x_format.is_empty() {
+            true => nice_axis(x_min, x_max, n_x),
+            false => nice_axis_with(x_min, x_max, n_x, |t, decimals| self.format_x(t, decimals)),
+        };
+        if self.y_axis == YAxis::Log {
+            return [x_axis, log_axis(y_min, y_max, n_y)];
+        }
+
+        // Ticks are placed on round numbers of the scaled unit
+        let (_, factor) = self.y_unit();
+        // Ticks with more decimals than the series shows are rounded like its values
+        let most = self.precision.map_or(6, usize::from);
+        let (y_axis, [y0, y1]) = nice_axis_with(y_min / factor, y_max / factor, n_y, |t, decimals| self.label_format.format(t, decimals.min(most)));
+        let y_bounds = [y0 * factor, y1 * factor];
+        [x_axis, (y_axis.bounds(y_bounds), y_bounds)]
+    }
+
+    fn graph_type(&self) -> GraphType {
+        match self.scatter {
+            true => GraphType::Scatter,
+            false => GraphType::Line,
+        }
+    }
+
+    // Unit the y axis is labelled in and its size in the stored unit
+    fn y_unit(&self) -> (String, f64) {
+        let summary = self.summary();
+        let max = match self.data.is_empty() {
+            true => 0.0,
+            false => summary.y_max.abs().max(summary.y_min.abs()),
+        };
+        // Labels with a format of their own show values as stored
+        match self.fixed_unit || !self.label_format.is_auto() {
+            true => (self.unit.clone(), 1.0),
+            false => unit_scale(&self.unit, max),
+        }
+    }
+
+    // Changes with anything written to data.csv for the series
+    fn points_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for (i, &(x, y)) in self.data.iter().enumerate() {
+            (x.to_bits(), y.to_bits(), self.error(i).map(f64::to_bits), self.pinned(i)).hash(&mut hasher);
+            self.values(i).iter().map(|v| v.to_bits()).collect::<Vec<_>>().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    fn json_points(&self) -> Vec<JsonPoint> {
+        self.data.iter().enumerate()
+            .map(|(i, &(x, y))| JsonPoint { x, y, error: self.error(i), values: self.values(i).to_vec(), pinned: self.pinned(i) })
+            .collect()
+    }
+
+    fn set_points(&mut self, mut points: Vec<JsonPoint>) {
+        self.points_changed();
+        points.sort_by(|a, b| a.x.total_cmp(&b.x));
+        self.data = points.iter().map(|p| (p.x, p.y)).collect();
+        self.point_meta = points.into_iter().map(|p| PointMeta { error: p.error, values: p.values, pinned: p.pinned }).collect();
+    }
+
+    // Number of points in `other` but not in self, and in self but not in `other`
+    fn diff_points(&self, other: &[(f64, f64)]) -> (usize, usize) {
+        let cmp = |a: &(f64, f64), b: &(f64, f64)| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1));
+        let mut old = self.data.clone();
+        let mut new = other.to_vec();
+        old.sort_by(cmp);
+        new.sort_by(cmp);
+
+        let (mut i, mut j, mut common) = (0, 0, 0);
+        while i < old.len() && j < new.len() {
+            match cmp(&old[i], &new[j]) {
+                std::cmp::Ordering::Less => i += 1,
+                std::cmp::Ordering::Greater => j += 1,
+                std::cmp::Ordering::Equal => {
+                    common += 1;
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        (new.len() - common, old.len() - common)
+    }
+}
+
+impl App {
+    fn new() -> Self {
+        Self {
+            mode: ViewMode::Graph,
+            selected_serie: 0,
+            status_msg: "h: help".to_string(),
+            unicode: unicode_locale(),
+            ..Default::default()
+        }
+    }
+    
+    fn write_csv(&mut self, path: String) -> Result<(), Box<dyn Error>> {
+        Self::write_points(path, &self.data_series, false)
+    }
+
+    // With `units`, every row also carries its series' unit and goal, which
+    // reading ignores
+    fn write_points(path: String, data_series: &[DataSeries], units: bool) -> Result<(), Box<dyn Error>> {
+        let file = File::create(path)?;
+        let mut wtr = csv::Writer::from_writer(file);
+        
+        let mut header = vec!["name", "x", "y", "error", "values", "pinned"];
+        if units {
+            header.extend(["unit", "goal"]);
+        }
+        wtr.write_record(header)?;
+        
+        // Flatten: write each data point as a separate row. Numbers are written
+        // as the shortest text that reads back as the same f64, so typed
+        // values come out as they were typed
+        for serie in data_series.iter().filter(|s| s.aggregate.is_none()) {
+            for (i, &(x, y)) in serie.data.iter().enumerate() {
+                let mut record = vec![
+                    serie.name.clone(),
+                    x.to_string(),
+                    y.to_string(),
+                    serie.error(i).map_or(String::new(), |e| e.to_string()),
+                    serie.values(i).iter().map(f64::to_string).collect::<Vec<_>>().join(";"),
+                    if serie.pinned(i) { "1" } else { "" }.to_string(),
+                ];
+                if units {
+                    record.extend([serie.unit.clone(), serie.goal.map_or(String::new(), |g| g.to_string())]);
+                }
+                wtr.write_record(record)?;
+            }
+        }
+        
+        wtr.flush()?;
+        Ok(())
+    }
+    
+    fn read_csv(path: String) -> Result<Vec<DataSeries>, Box<dyn Error>> {
+        let (data_series, rejected) = Self::read_csv_lenient(path)?;
+        Self::strict(data_series, rejected)
+    }
+
+    // Fails on the first rejected row, for files that are not ours to repair
+    fn strict(data_series: Vec<DataSeries>, rejected: Vec<RejectedRow>) -> Result<Vec<DataSeries>, Box<dyn Error>> {
+        match rejected.first() {
+            Some(row) => Err(format!("line {}: {}", row.line, row.error).into()),
+            None => Ok(data_series),
+        }
+    }
+
+    // Like read_csv, but rows that do not parse are set aside instead of failing the file
+    fn read_csv_lenient(path: String) -> Result<(Vec<DataSeries>, Vec<RejectedRow>), Box<dyn Error>> {
+        Ok(Self::parse_csv(&fs::read(path)?, None))
+    }
+
+    // Large files are split into chunks at line ends and parsed on all cores,
+    // `progress` gets the share parsed so far every now and then
+    fn parse_csv(content: &[u8], progress: Option<&(dyn Fn(f64) + Sync)>) -> (Vec<DataSeries>, Vec<RejectedRow>) {
+        // The first line is the header
+        let next_line = |from: usize| content[from..].iter().position(|&b| b == b'\n').map_or(content.len(), |i| from + i + 1);
+        let body = next_line(0);
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        let n_chunks = ((content.len() - body) / CHUNK_BYTES).clamp(1, threads);
+        let mut bounds = vec![body];
+        for k in 1..n_chunks {
+            let guess = body + (content.len() - body) * k / n_chunks;
+            bounds.push(next_line(guess.max(bounds[k - 1])));
+        }
+        bounds.push(content.len());
+
+        // Line number of each chunk's first row, for the rejected rows
+        let mut first_lines = vec![2];
+        for w in bounds.windows(2) {
+            let newlines = content[w[0]..w[1]].iter().filter(|&&b| b == b'\n').count() as u64;
+            first_lines.push(first_lines[first_lines.len() - 1] + newlines);
+        }
+
+        let done: Vec<AtomicUsize> = (0..n_chunks).map(|_| AtomicUsize::new(0)).collect();
+        // A single chunk gets a thread too, so single core machines still see progress
+        let chunks: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = bounds.windows(2).zip(&first_lines).zip(&done)
+                .map(|((w, &first_line), done)| scope.spawn(move || Self::parse_chunk(&content[w[0]..w[1]], first_line, done)))
+                .collect();
+            if let Some(progress) = progress {
+                while handles.iter().any(|h| !h.is_finished()) {
+                    progress(done.iter().map(|d| d.load(Ordering::Relaxed)).sum::<usize>() as f64 / content.len() as f64);
+                    thread::sleep(PROGRESS_POLL);
+                }
+            }
+            // A chunk that failed is one rejected row, so the rows it held are not saved away
+            handles.into_iter().zip(&first_lines)
+                .map(|(h, &line)| h.join().unwrap_or_else(|_| {
+                    (Vec::new(), vec![RejectedRow { line, text: String::new(), error: "this row and the ones after it in its chunk could not be read".to_string() }])
+                }))
+                .collect()
+        });
+
+        // Series in order of first appearance, series.toml may reorder them later
+        use std::collections::HashMap;
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut names: Vec<String> = Vec::new();
+        let mut series_points: Vec<Vec<(f64, f64, PointMeta)>> = Vec::new();
+        let mut rejected = Vec::new();
+        for (rows, bad) in chunks {
+            for (name, x, y, meta) in rows {
+                let i = match index.get(&name) {
+                    Some(&i) => i,
+                    None => {
+                        index.insert(name.clone(), names.len());
+                        names.push(name);
+                        series_points.push(Vec::new());
+                        names.len() - 1
+                    }
+                };
+                series_points[i].push((x, y, meta));
+            }
+            rejected.extend(bad);
+        }
+
+        // Large series are sorted on threads of their own
+        let sort = |points: &mut Vec<(f64, f64, PointMeta)>| points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        thread::scope(|scope| {
+            for points in &mut series_points {
+                match points.len() * 64 >= CHUNK_BYTES {
+                    true => {
+                        scope.spawn(move || sort(points));
+                    }
+                    false => sort(points),
+                }
+            }
+        });
+
+        let mut data_series = Vec::new();
+        for (name, points) in names.into_iter().zip(series_points) {
+            let data = points.iter().map(|&(x, y, _)| (x, y)).collect();
+            let point_meta = points.into_iter().map(|(_, _, meta)| meta).collect();
+            data_series.push(DataSeries { name, data, point_meta, ..Default::default() });
+        }
+
+        if !rejected.is_empty() {
+            let text = String::from_utf8_lossy(content);
+            let lines: Vec<&str> = text.lines().collect();
+            for row in &mut rejected {
+                row.text = lines.get((row.line as usize).wrapping_sub(1)).map_or(String::new(), |l| l.to_string());
+            }
+        }
+        (data_series, rejected)
+    }
+
+    // Rows of one chunk without a header, counting lines from `first_line`.
+    // `done` follows the bytes parsed
+    fn parse_chunk(chunk: &[u8], first_line: u64, done: &AtomicUsize) -> (Vec<CsvRow>, Vec<RejectedRow>) {
+        // Flexible so files from before the error, values and pinned columns still load
+        let mut rdr = csv::ReaderBuilder::new().has_headers(false).flexible(true).from_reader(chunk);
+        let (mut rows, mut rejected) = (Vec::new(), Vec::new());
+        for result in rdr.records() {
+            let (position, parsed) = match result {
+                Ok(record) => (record.position().cloned(), Self::parse_record(&record)),
+                Err(e) => (e.position().cloned(), Err(e.to_string())),
+            };
+            match parsed {
+                Ok(row) => rows.push(row),
+                Err(error) => {
+                    let line = position.as_ref().map_or(0, |p| first_line + p.line() - 1);
+                    rejected.push(RejectedRow { line, text: String::new(), error });
+                }
+            }
+            if rows.len() % 4096 == 0 && let Some(p) = position {
+                done.store(p.byte() as usize, Ordering::Relaxed);
+            }
+        }
+        (rows, rejected)
+    }
+
+    fn parse_record(record: &csv::StringRecord) -> Result<CsvRow, String> {
+        // NaN and inf parse as f64 but cannot be sorted or drawn
+        let number = |field: &str, s: &str| s.trim().parse::<f64>().ok()
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| format!("{} is not a number: {}", field, s));
+        let name = record.get(0).filter(|n| !n.is_empty()).ok_or("Missing name")?.to_string();
+        let x = number("x", record.get(1).ok_or("Missing x")?)?;
+        let y = number("y", record.get(2).ok_or("Missing y")?)?;
+        let error = match record.get(3) {
+            Some(e) if !e.is_empty() => Some(number("error", e)?),
+            _ => None,
+        };
+        let values = match record.get(4) {
+            Some(v) if !v.is_empty() => v.split(';').map(|v| number("value", v)).collect::<Result<_, _>>()?,
+            _ => Vec::new(),
+        };
+        let pinned = record.get(5).is_some_and(|p| p == "1");
+        Ok((name, x, y, PointMeta { error, values, pinned }))
+    }
+
+    fn write_meta(&self, path: String) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.meta_toml()?)?;
+        Ok(())
+    }
+
+    fn meta_toml(&self) -> Result<String, Box<dyn Error>> {
+        let mut file = toml::Table::new();
+        file.insert("series".to_string(), toml::Value::try_from(&self.data_series)?);
+        Ok(toml::to_string(&file)?)
+    }
+
+    fn points_state(&self) -> Vec<(String, u64)> {
+        self.data_series.iter()
+            .filter(|s| s.aggregate.is_none())
+            .map(|s| (s.name.clone(), s.points_hash()))
+            .collect()
+    }
+
+    // Writes data.csv unless no series' points changed since it was read or
+    // written. Returns the series that changed, None when nothing was written
+    fn save_points(&mut self) -> Result<Option<Vec<String>>, Box<dyn Error>> {
+        let state = self.points_state();
+        if self.saved_points.as_ref() == Some(&state) && Path::new("data.csv").exists() {
+            return Ok(None);
+        }
+        let changed = state.iter()
+            .filter(|entry| !self.saved_points.as_ref().is_some_and(|saved| saved.contains(entry)))
+            .map(|(name, _)| name.clone())
+            .collect();
+        self.write_csv("data.csv".to_string())?;
+        self.saved_points = Some(state);
+        Ok(Some(changed))
+    }
+
+    // Writes series.toml unless it would come out the same, true if it was written
+    fn save_meta(&mut self) -> Result<bool, Box<dyn Error>> {
+        let content = self.meta_toml()?;
+        let hash = text_hash(&content);
+        if self.saved_meta == Some(hash) && Path::new("series.toml").exists() {
+            return Ok(false);
+        }
+        fs::write("series.toml", content)?;
+        self.saved_meta = Some(hash);
+        Ok(true)
+    }
+
+    // Attaches the metadata in series.toml to the loaded series by name,
+    // series without points yet are created empty. The file's order wins
+    fn read_meta(data_series: &mut Vec<DataSeries>, path: String) -> Result<(), Box<dyn Error>> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        let file: SeriesFile = toml::from_str(&content)?;
+
+        let mut ordered = Vec::new();
+        for mut meta in file.series {
+            if let Some(i) = data_series.iter().position(|s| s.name == meta.name) {
+                let serie = data_series.remove(i);
+                meta.data = serie.data;
+                meta.point_meta = serie.point_meta;
+            }
+            ordered.push(meta);
+        }
+        // Series only in data.csv, like ones added by hand, go last
+        ordered.append(data_series);
+        *data_series = ordered;
+        Ok(())
+    }
+
+    fn write_json(&self, path: String) -> Result<(), Box<dyn Error>> {
+        let series = self.json_series()?;
+        fs::write(path, serde_json::to_string_pretty(&serde_json::json!({ "series": series }))?)?;
+        Ok(())
+    }
+
+    fn json_series(&self) -> Result<Vec<serde_json::Value>, Box<dyn Error>> {
+        let mut series = Vec::new();
+        for serie in &self.data_series {
+            let points = serie.json_points();
+            let mut value = serde_json::to_value(serie)?;
+            value["points"] = serde_json::to_value(points)?;
+            series.push(value);
+        }
+        Ok(series)
+    }
+
+    fn read_json(path: String) -> Result<Vec<DataSeries>, Box<dyn Error>> {
+        let file: JsonFile = serde_json::from_str(&fs::read_to_string(path)?)?;
+        Ok(Self::from_json(file.series))
+    }
+
+    fn from_json(series: Vec<JsonSeries>) -> Vec<DataSeries> {
+        let mut data_series = Vec::new();
+        for JsonSeries { mut serie, points } in series {
+            serie.set_points(points);
+            data_series.push(serie);
+        }
+        data_series
+    }
+
+    // One JSON file holding every series, the config and the view state
+    fn save_workspace(&mut self, path: &str) {
+        let ui = UiState {
+            selected: self.data_series[self.selected_serie].name.clone(),
+            mode: match self.mode {
+                ViewMode::Batch => ViewMode::Graph,
+                mode => mode,
+            },
+            x_window: self.x_window,
+            y_window: self.y_window,
+            table_all: self.table_all,
+            table_order: self.table_order,
+            hidden: self.hidden.clone(),
+        };
+        let result = self.json_series().and_then(|series| {
+            let workspace = serde_json::json!({ "config": self.config, "ui": ui, "series": series });
+            fs::write(path, serde_json::to_string_pretty(&workspace)?)?;
+            Ok(())
+        });
+
+        self.status_msg = match result {
+            Ok(()) => format!("Saved workspace to {}", path),
+            Err(e) => format!("Could not save workspace to {}: {}", path, e),
+        };
+    }
+
+    // Replaces every series and the config with the ones from a workspace file
+    fn load_workspace(&mut self, path: &str) {
+        let file = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str::<WorkspaceFile>(&content).map_err(|e| e.to_string()));
+        let file = match file {
+            Ok(file) => file,
+            Err(e) => {
+                self.status_msg = format!("Could not load workspace {}: {}", path, e);
+                return;
+            }
+        };
+
+        self.data_series = Self::from_json(file.series);
+        if self.data_series.is_empty() {
+            self.data_series.push(DataSeries::new());
+        }
+        self.selected_serie = self.data_series.iter().position(|s| s.name == file.ui.selected).unwrap_or(0);
+        self.mode = file.ui.mode;
+        self.x_window = file.ui.x_window;
+        self.y_window = file.ui.y_window;
+        self.table_all = file.ui.table_all;
+        self.table_order = file.ui.table_order;
+        self.hidden = file.ui.hidden;
+        self.table_state.select(None);
+        self.config = file.config;
+        self.find_duplicates();
+
+        self.status_msg = match self.save_config() {
+            Ok(()) => format!("Loaded workspace {}", path),
+            Err(e) => format!("Loaded workspace {}, could not write {}: {}", path, CONFIG_PATH, e),
+        };
+    }
+
+    // JSON bundles everything in one file, other paths get the data.csv
+    // format plus a .toml sidecar holding the series metadata
+    // JSON keeps units and goals with the rest of the series, Markdown puts them
+    // in each heading and CSV in extra columns when asked for with `units`
+    fn export(&mut self, arg: &str) {
+        let (path, units) = match arg.strip_suffix(" units") {
+            Some(path) => (path.trim(), true),
+            None => (arg, false),
+        };
+        let extension = Path::new(path).extension().and_then(|e| e.to_str());
+        // CSV exports take their metadata along in a .toml next to them
+        let meta_path = Path::new(path).with_extension("toml");
+        let mut targets = vec![Path::new(path)];
+        if !matches!(extension, Some("json" | "md")) {
+            targets.push(&meta_path);
+        }
+        if let Some(own) = targets.into_iter().find(|target| is_own_file(target)) {
+            self.status_msg = format!("Will not export over {}, tracktui keeps its data there", own.display());
+            return;
+        }
+
+        let result = match extension {
+            Some("json") => self.write_json(path.to_string()),
+            Some("md") => self.write_markdown(path),
+            _ => Self::write_points(path.to_string(), &self.data_series, units)
+                .and_then(|_| self.write_meta(meta_path.to_string_lossy().to_string())),
+        };
+
+        self.status_msg = match result {
+            Ok(()) => format!("Exported {} series to {}", self.data_series.len(), path),
+            Err(e) => format!("Could not export to {}: {}", path, e),
+        };
+    }
+
+    // One table per series under a heading naming its unit and goal
+    fn write_markdown(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        // Pipes would end a cell and a line break the row, as in write_keys
+        let escape = |text: &str| text.replace('|', "\\|").replace('\n', " ");
+        let mut out = String::new();
+        for serie in self.data_series.iter().filter(|s| !s.data.is_empty()) {
+            let precision = serie.precision(self.config.precision);
+            let mut heading = format!("## {}", escape(&serie.name));
+            if !serie.unit.is_empty() {
+                heading += &format!(" ({})", escape(&serie.unit));
+            }
+            if let Some(goal) = serie.goal {
+                heading += &format!(" · goal {}", format_value(goal, precision));
+            }
+            out += &format!("{}\n\n", heading);
+            if !serie.description.is_empty() {
+                out += &format!("{}\n\n", escape(&serie.description));
+            }
+
+            let y_title = match serie.unit.is_empty() {
+                true => serie.y_name().to_string(),
+                false => format!("{} ({})", serie.y_name(), serie.unit),
+            };
+            let mut columns = vec!["x".to_string(), escape(&y_title)];
+            columns.extend(serie.extra_columns().iter().map(|c| escape(c)));
+            out += &format!("| {} |\n|{}\n", columns.join(" | "), " --- |".repeat(columns.len()));
+            for (i, &(x, y)) in serie.data.iter().enumerate() {
+                let mut cells = vec![escape(&serie.describe_x(x, precision)), format_value(y, precision)];
+                cells.extend(serie.values(i).iter().map(|&v| format_value(v, precision)));
+                out += &format!("| {} |\n", cells.join(" | "));
+            }
+            out += "\n";
+        }
+        fs::write(path, out)?;
+        Ok(())
+    }
+
+    // import <path> [x=<col> y=<col> ...], the mapping is taken from the end
+    // so the path may contain spaces
+    fn import(&mut self, arg: &str) {
+        let mut path = arg.trim();
+        while let Some((rest, token)) = path.rsplit_once(' ')
+            && token.split_once('=').is_some_and(|(key, _)| ["x", "y", "series", "save"].contains(&key)) {
+            path = rest.trim_end();
+        }
+        let mapping = arg.trim()[path.len()..].trim();
+        self.import_file(path, mapping);
+    }
+
+    fn import_file(&mut self, path: &str, mapping: &str) {
+        if !path.ends_with(".json") {
+            let header = csv::Reader::from_path(path)
+                .and_then(|mut rdr| Ok(rdr.headers()?.iter().map(str::to_string).collect::<Vec<_>>()));
+            match header {
+                Ok(header) if header.len() < 3 || header[..3] != ["name", "x", "y"] => {
+                    self.import_mapped(path, header, mapping);
+                    return;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    self.status_msg = format!("Could not import {}: {}", path, e);
+                    return;
+                }
+            }
+        }
+
+        if let Some(job) = &self.import_job {
+            self.status_msg = format!("Still importing {}", job.path);
+            return;
+        }
+        if fs::metadata(path).is_ok_and(|m| m.len() >= LARGE_IMPORT) {
+            self.start_import(path);
+            return;
+        }
+
+        let loaded = match path.ends_with(".json") {
+            true => Self::read_json(path.to_string()),
+            false => Self::read_csv(path.to_string()),
+        };
+        self.finish_import(path, loaded);
+    }
+
+    // Parses on a background thread, poll_import picks up the result
+    fn start_import(&mut self, path: &str) {
+        let (tx, rx) = mpsc::channel();
+        let owned = path.to_string();
+        thread::spawn(move || {
+            let loaded = match owned.ends_with(".json") {
+                true => Self::read_json(owned).map_err(|e| e.to_string()),
+                false => fs::read(&owned).map_err(|e| e.to_string()).and_then(|content| {
+                    let (loaded, rejected) = Self::parse_csv(&content, Some(&|p| {
+                        let _ = tx.send(ImportMsg::Progress(p));
+                    }));
+                    Self::strict(loaded, rejected).map_err(|e| e.to_string())
+                }),
+            };
+            let _ = tx.send(ImportMsg::Done(loaded));
+        });
+        self.import_job = Some(ImportJob { path: path.to_string(), rx });
+        self.status_msg = format!("Importing {}", path);
+    }
+
+    // True when there was news from the import running in the background
+    fn poll_import(&mut self) -> bool {
+        let Some(job) = &self.import_job else { return false };
+        let path = job.path.clone();
+        let (mut progress, mut done) = (None, None);
+        loop {
+            match job.rx.try_recv() {
+                Ok(ImportMsg::Progress(p)) => progress = Some(p),
+                Ok(ImportMsg::Done(loaded)) => done = Some(loaded),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    done = done.or_else(|| Some(Err("the import stopped".to_string())));
+                    break;
+                }
+            }
+        }
+        if let Some(loaded) = done {
+            self.import_job = None;
+            self.finish_import(&path, loaded.map_err(Into::into));
+            return true;
+        }
+        if let Some(p) = progress {
+            self.status_msg = format!("Importing {}, {:.0}%", path, p * 100.0);
+        }
+        progress.is_some()
+    }
+
+    fn finish_import(&mut self, path: &str, loaded: Result<Vec<DataSeries>, Box<dyn Error>>) {
+        // A tracktui CSV may come with its series.toml, named like it
+        let loaded = loaded.and_then(|mut loaded| {
+            if !path.ends_with(".json") {
+                let meta_path = Path::new(path).with_extension("toml");
+                Self::read_meta(&mut loaded, meta_path.to_string_lossy().to_string())?;
+            }
+            Ok(loaded)
+        });
+
+        match loaded {
+            Ok(loaded) => {
+                let mut report = self.merge_series(loaded, true);
+                report.source = format!("Import {}", path);
+                self.status_msg = format!("Imported {}", path);
+                self.merge_report = Some(report);
+            }
+            Err(e) => self.status_msg = format!("Could not import {}: {}", path, e),
+        }
+    }
+
+    // Imports a foreign CSV file with the columns given as x=<col> y=<col>
+    // [series=<col>], or with the saved profile for the same header.
+    // save=<profile> keeps the given columns for next time
+    fn import_mapped(&mut self, path: &str, header: Vec<String>, mapping: &str) {
+        let options: Option<Vec<(&str, &str)>> = mapping.split_whitespace().map(|w| w.split_once('=')).collect();
+        let Some(options) = options else {
+            self.status_msg = "Map columns as x=<col> y=<col> [series=<col>] [save=<profile>]".to_string();
+            return;
+        };
+        let option = |key: &str| options.iter().find(|(k, _)| *k == key).map(|(_, v)| v.to_string());
+
+        let profile = match (option("x"), option("y")) {
+            (Some(x), Some(y)) => ImportProfile { name: option("save").unwrap_or_default(), header, x, y, series: option("series") },
+            _ => match self.config.import_profiles.iter().find(|p| p.header == header) {
+                Some(profile) => profile.clone(),
+                None => {
+                    self.status_msg = format!(
+                        "Unknown columns {}, map them: import {} x=<col> y=<col> [series=<col>] [save=<profile>]",
+                        header.join(", "), path
+                    );
+                    return;
+                }
+            },
+        };
+
+        let fallback_name = Path::new(path).file_stem().map_or(path.to_string(), |s| s.to_string_lossy().to_string());
+        let (loaded, skipped) = match Self::read_mapped(path, &profile, &fallback_name) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                self.status_msg = format!("Could not import {}: {}", path, e);
+                return;
+            }
+        };
+
+        // Metadata stays as it is, the file has none
+        let mut report = self.merge_series(loaded, false);
+        report.source = format!("Import {}", path);
+        self.merge_report = Some(report);
+        self.status_msg = match profile.name.as_str() {
+            "" => format!("Imported {}", path),
+            name => format!("Imported {} with profile {}", path, name),
+        };
+        if skipped > 0 {
+            self.status_msg.push_str(&format!(", skipped {} rows that did not parse", skipped));
+        }
+
+        if option("save").is_some() {
+            self.config.import_profiles.retain(|p| p.name != profile.name && p.header != profile.header);
+            let name = profile.name.clone();
+            self.config.import_profiles.push(profile);
+            if let Err(e) = self.save_config() {
+                self.status_msg = format!("Could not save profile {}: {}", name, e);
+            }
+        }
+    }
+
+    // Points of a CSV file laid out as the profile says, and the number of rows skipped
+    fn read_mapped(path: &str, profile: &ImportProfile, fallback_name: &str) -> Result<(Vec<DataSeries>, usize), Box<dyn Error>> {
+        let mut rdr = csv::ReaderBuilder::new().flexible(true).from_path(path)?;
+        let headers = rdr.headers()?.clone();
+        let column = |name: &str| headers.iter().position(|h| h == name).ok_or(format!("No column {}", name));
+        let x_col = column(&profile.x)?;
+        let y_col = column(&profile.y)?;
+        let series_col = profile.series.as_deref().map(column).transpose()?;
+
+        let mut data_series: Vec<DataSeries> = Vec::new();
+        let mut skipped = 0;
+        for result in rdr.records() {
+            let record = result?;
+            // NaN and inf parse as f64 but cannot be sorted or drawn, as in parse_record
+            let x = record.get(x_col).and_then(|x| parse_x(x.trim())).filter(|x| x.is_finite());
+            let y = record.get(y_col).and_then(|y| y.trim().parse::<f64>().ok()).filter(|y| y.is_finite());
+            let name = match series_col {
+                Some(col) => record.get(col).unwrap_or_default().to_string(),
+                None => fallback_name.to_string(),
+            };
+            let (Some(x), Some(y), false) = (x, y, name.is_empty()) else {
+                skipped += 1;
+                continue;
+            };
+
+            match data_series.iter_mut().find(|s| s.name == name) {
+                Some(serie) => serie.insert_point(x, y, PointMeta::default()),
+                None => {
+                    let mut serie = DataSeries { name, created_at: today(), ..Default::default() };
+                    serie.insert_point(x, y, PointMeta::default());
+                    data_series.push(serie);
+                }
+            }
+        }
+        Ok((data_series, skipped))
+    }
+
+    // What merge_series would change, without changing anything
+    fn diff_series(&self, loaded: &[DataSeries]) -> MergeReport {
+        let mut report = MergeReport::default();
+        for serie in &self.data_series {
+            if !serie.data.is_empty() && !loaded.iter().any(|s| s.name == serie.name) {
+                report.missing.push(serie.name.clone());
+            }
+        }
+        for new in loaded {
+            match self.data_series.iter().find(|s| s.name == new.name) {
+                Some(serie) => {
+                    let (added, removed) = serie.diff_points(&new.data);
+                    if added > 0 || removed > 0 {
+                        report.updated.push((new.name.clone(), added, removed));
+                    }
+                }
+                None => report.added.push(new.name.clone()),
+            }
+        }
+        report
+    }
+
+    fn data_file_mtime() -> Option<SystemTime> {
+        fs::metadata("data.csv").and_then(|m| m.modified()).ok()
+    }
+
+    // Offers the changes when another program rewrote data.csv, true if it did
+    fn check_data_file(&mut self) -> bool {
+        let mtime = Self::data_file_mtime();
+        if self.demo || self.merge_report.is_some() || self.recovery.is_some() || self.loading.is_some() || mtime.is_none() || mtime == self.data_mtime {
+            return false;
+        }
+        self.data_mtime = mtime;
+        // The file no longer holds what is in memory, whatever is decided below
+        self.saved_points = None;
+
+        let Ok(loaded) = Self::read_csv("data.csv".to_string()) else { return false };
+        let mut report = self.diff_series(&loaded);
+        if report.is_empty() {
+            return false;
+        }
+        report.source = "data.csv changed on disk".to_string();
+        report.pending = Some(loaded);
+        self.merge_report = Some(report);
+        true
+    }
+
+    // Re-reads data.csv and reconciles it with the series in memory by name:
+    // known series take the file's points, unknown ones are appended and
+    // series absent from the file are kept untouched
+    fn reload_csv(&mut self) {
+        // The demo series are not in data.csv
+        if self.demo {
+            self.status_msg = "Demo mode, data.csv is left alone".to_string();
+            return;
+        }
+        if self.loading.is_some() {
+            self.status_msg = "Still loading data.csv".to_string();
+            return;
+        }
+        let loaded = match Self::read_csv("data.csv".to_string()) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                self.status_msg = format!("Could not reload data.csv: {}", e);
+                return;
+            }
+        };
+
+        self.data_mtime = Self::data_file_mtime();
+        self.saved_points = None;
+        let mut report = self.merge_series(loaded, false);
+        report.source = "Reload data.csv".to_string();
+        self.status_msg = "Reloaded data.csv".to_string();
+        self.merge_report = Some(report);
+    }
+
+    // Hands the selected series to $VISUAL or $EDITOR as CSV with the TUI
+    // suspended, then takes the edited points back
+    fn edit_externally(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let serie = &self.data_series[self.selected_serie];
+        // Its points would be recomputed over the edits and never saved
+        if serie.aggregate.is_some() {
+            self.status_msg = "This series is computed from its group".to_string();
+            return Ok(());
+        }
+        let path = std::env::temp_dir().join(format!("tracktui-{}.csv", std::process::id()));
+        let path_str = path.to_string_lossy().to_string();
+        if let Err(e) = Self::write_points(path_str.clone(), std::slice::from_ref(serie), false) {
+            self.status_msg = format!("Could not write {}: {}", path_str, e);
+            return Ok(());
+        }
+
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| "vi".to_string());
+        set_mouse_capture(false);
+        ratatui::restore();
+        // Through sh so editors given with arguments, like "code --wait", work
+        let status = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(format!("{} \"$1\"", editor))
+            .arg("tracktui")
+            .arg(&path)
+            .status();
+        resume_terminal(terminal)?;
+        set_mouse_capture(self.config.mouse);
+
+        let loaded = match status {
+            Ok(status) if status.success() => Self::read_csv(path_str),
+            Ok(status) => Err(format!("{} exited with {}", editor, status).into()),
+            Err(e) => Err(format!("Could not run {}: {}", editor, e).into()),
+        };
+        let _ = fs::remove_file(&path);
+
+        // Rows renamed to another series are left out
+        let serie = &mut self.data_series[self.selected_serie];
+        match loaded {
+            Ok(loaded) => {
+                let new = loaded.into_iter().find(|s| s.name == serie.name).unwrap_or_default();
+                let (added, removed) = serie.diff_points(&new.data);
+                serie.data = new.data;
+                serie.point_meta = new.point_meta;
+                serie.points_changed();
+                self.table_state.select(None);
+
+                let mut report = MergeReport { source: format!("Edited in {}", editor), ..Default::default() };
+                if added > 0 || removed > 0 {
+                    report.updated.push((serie.name.clone(), added, removed));
+                }
+                self.status_msg = format!("Edited {} in {}", serie.name, editor);
+                self.merge_report = Some(report);
+            }
+            Err(e) => self.status_msg = format!("Edit discarded: {}", e),
+        }
+        Ok(())
+    }
+
+    // Raw mode turns Ctrl+Z into a key instead of SIGTSTP, so stop the process
+    // by hand with the terminal restored, and take it back once continued
+    fn suspend(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        set_mouse_capture(false);
+        ratatui::restore();
+        #[cfg(unix)]
+        // SAFETY: raise only sends a signal to this process
+        unsafe {
+            libc::raise(libc::SIGTSTP);
+        }
+        resume_terminal(terminal)?;
+        set_mouse_capture(self.config.mouse);
+        Ok(())
+    }
+
+    // Reconciles loaded series with the ones in memory by name, with_meta
+    // also takes over the loaded metadata instead of only the points
+    fn merge_series(&mut self, loaded: Vec<DataSeries>, with_meta: bool) -> MergeReport {
+        let report = self.diff_series(&loaded);
+        let mut seen: Vec<String> = Vec::new();
+        for new in loaded {
+            // A name twice in the same file is kept twice, find_duplicates asks what to do
+            if seen.contains(&new.name) {
+                self.data_series.push(new);
+                continue;
+            }
+            seen.push(new.name.clone());
+            match self.data_series.iter_mut().find(|s| s.name == new.name) {
+                Some(serie) => {
+                    match with_meta {
+                        true => *serie = new,
+                        false => {
+                            serie.data = new.data;
+                            serie.point_meta = new.point_meta;
+                            serie.points_changed();
+                        }
+                    }
+                }
+                None => self.data_series.push(new),
+            }
+        }
+
+        let len = self.data_series[self.selected_serie].data.len();
+        if self.table_state.selected().is_some_and(|i| i >= len) {
+            self.table_state.select(None);
+        }
+        self.find_duplicates();
+        report
+    }
+
+    // Moves the data files into dir and carries on from there. Every path is
+    // relative to the working directory, so nothing else has to change
+    fn relocate(&mut self, dir: &str) {
+        if self.demo {
+            self.status_msg = "Demo data is never saved".to_string();
+            return;
+        }
+        let dir = match (dir.strip_prefix("~/"), std::env::var("HOME")) {
+            (Some(rest), Ok(home)) => PathBuf::from(home).join(rest),
+            _ => PathBuf::from(dir),
+        };
+        let Ok(here) = std::env::current_dir() else { return };
+        let files = match self.relocate_files(&here, &dir) {
+            Ok(files) => files,
+            Err(e) => {
+                self.status_msg = format!("Could not move the data to {}: {}", dir.display(), e);
+                return;
+            }
+        };
+        // Originals only go once every copy read back the same
+        let left: Vec<&str> = files.iter().copied().filter(|file| fs::remove_file(here.join(file)).is_err()).collect();
+        self.status_msg = match left.is_empty() {
+            true => format!("Moved {} to {}", files.join(", "), current_dir_name()),
+            false => format!("Copied {} to {}, could not remove the old {}", files.join(", "), current_dir_name(), left.join(", ")),
+        };
+    }
+
+    // Copies the data files from here into dir and switches to it, returns the files copied
+    fn relocate_files(&mut self, here: &Path, dir: &Path) -> Result<Vec<&'static str>, Box<dyn Error>> {
+        fs::create_dir_all(dir)?;
+        let dir = dir.canonicalize()?;
+        if dir == here {
+            return Err("the data already is there".into());
+        }
+
+        // The copies take in everything not saved yet
+        self.save_points()?;
+        self.save_meta()?;
+        let files: Vec<&str> = OWN_FILES.into_iter()
+            .filter(|file| Path::new(file).exists())
+            .collect();
+        if let Some(file) = files.iter().find(|file| dir.join(file).exists()) {
+            return Err(format!("it already has a {}", file).into());
+        }
+        let copied: Result<(), Box<dyn Error>> = files.iter().try_for_each(|file| {
+            fs::copy(file, dir.join(file))?;
+            match fs::read(file)? == fs::read(dir.join(file))? {
+                true => Ok(()),
+                false => Err(format!("{} did not copy intact", file).into()),
+            }
+        });
+        if let Err(e) = copied {
+            for file in &files {
+                let _ = fs::remove_file(dir.join(file));
+            }
+            return Err(e);
+        }
+
+        std::env::set_current_dir(&dir)?;
+        self.data_mtime = Self::data_file_mtime();
+        Ok(files)
+    }
+
+    // Copies data.csv aside as it is on disk, before anything overwrites it
+    fn back_up_data(&mut self) -> Option<String> {
+        if let Some(recovery) = &self.recovery && recovery.backup.is_some() {
+            return recovery.backup.clone();
+        }
+        let path = format!("data.csv.{}.bak", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+        match fs::copy("data.csv", &path) {
+            Ok(_) => {
+                self.status_msg = format!("Backed up data.csv to {}", path);
+                if let Some(recovery) = &mut self.recovery {
+                    recovery.backup = Some(path.clone());
+                }
+                Some(path)
+            }
+            Err(e) => {
+                self.status_msg = format!("Could not back up data.csv: {}", e);
+                None
+            }
+        }
+    }
+
+    fn resolve_recovery(&mut self, choice: usize) {
+        let Some(recovery) = &self.recovery else { return };
+        match RECOVERY_CHOICES[choice] {
+            "Keep good rows" => {
+                // The bad rows are kept for fixing by hand, data.csv is rewritten without them on save
+                let rejected: String = recovery.rejected.iter()
+                    .filter(|row| row.line > 0)
+                    .map(|row| format!("{}\n", row.text))
+                    .collect();
+                if !rejected.is_empty() && let Err(e) = fs::write("data.csv.rejected", &rejected) {
+                    self.status_msg = format!("Could not write data.csv.rejected: {}", e);
+                    return;
+                }
+                self.status_msg = match rejected.is_empty() {
+                    true => "Continuing with the rows that were read".to_string(),
+                    false => "Continuing without the bad rows, they are in data.csv.rejected".to_string(),
+                };
+            }
+            "Back up" => {
+                self.back_up_data();
+                return;
+            }
+            "Start fresh" => {
+                let Some(path) = self.back_up_data() else { return };
+                self.data_series = vec![DataSeries::new()];
+                self.selected_serie = 0;
+                self.status_msg = format!("Started fresh, the old data is in {}", path);
+            }
+            _ => {
+                self.exit = true;
+                return;
+            }
+        }
+        if let Some(recovery) = self.recovery.take() {
+            self.read_only = recovery.read_only;
+        }
+    }
+
+    // Opens the duplicate dialog for the first two series sharing a name
+    fn find_duplicates(&mut self) {
+        self.duplicate = self.data_series.iter().enumerate()
+            .find_map(|(j, b)| self.data_series[..j].iter().position(|a| a.name == b.name).map(|i| (i, j)));
+        self.duplicate_idx = 0;
+    }
+
+    fn resolve_duplicate(&mut self, choice: usize) {
+        let Some((i, j)) = self.duplicate.take() else { return };
+        match DUPLICATE_CHOICES[choice] {
+            "Rename" => {
+                self.select_serie(j);
+                self.start_rename();
+                return;
+            }
+            "Merge" => {
+                self.select_serie(i);
+                self.merge_into(j, "both");
+            }
+            _ => {
+                let base = self.data_series[j].name.clone();
+                let mut n = 2;
+                while self.data_series.iter().any(|s| s.name == format!("{} {}", base, n)) {
+                    n += 1;
+                }
+                self.data_series[j].name = format!("{} {}", base, n);
+                self.status_msg = format!("Renamed the second {} to {} {}", base, base, n);
+            }
+        }
+        self.find_duplicates();
+    }
+
+    // Writes tracktui.toml unless it failed to load, which would lose the user's
+    // settings, or this is the demo
+    fn save_config(&self) -> Result<(), Box<dyn Error>> {
+        if self.demo {
+            return Err("demo mode, changes are not saved".into());
+        }
+        match &self.config_error {
+            Some(e) => Err(format!("it did not load ({}), fix it and restart", e).into()),
+            None => self.config.save(CONFIG_PATH),
+        }
+    }
+
+    // Reads the config and the series, problems end up in the status bar
+    // A large data.csv is left to load_in_background when `background` is set
+    fn load(&mut self, background: bool) {
+        // Read config
+        match Config::load(CONFIG_PATH) {
+            Ok(config) => self.config = config,
+            Err(e) => {
+                self.status_msg = format!("Could not load {}, settings are not saved until it is fixed: {}", CONFIG_PATH, e);
+                self.config_error = Some(e.to_string().lines().next().unwrap_or_default().to_string());
+            }
+        }
+        // chrono takes the local zone from TZ, read from the system's zone database
+        if !self.config.timezone.is_empty() {
+            // No other thread has started yet to read the environment meanwhile
+            unsafe { std::env::set_var("TZ", &self.config.timezone) };
+        }
+        self.load_theme();
+        self.table_order = self.config.table_order;
+        if !self.demo {
+            self.mode = self.config.default_view;
+        }
+
+        // Read csv
+        if self.demo {
+            self.data_series = DataSeries::demo();
+            self.tour = Some(0);
+            self.status_msg = "Demo mode, changes are not saved".to_string();
+        } else if background && fs::metadata("data.csv").is_ok_and(|m| m.len() >= LARGE_IMPORT) {
+            self.load_in_background();
+        } else {
+            let mut rejected = Vec::new();
+            match Self::read_csv_lenient("data.csv".to_string()) {
+                Ok((data_series, bad)) => (self.data_series, rejected) = (data_series, bad),
+                Err(e) if e.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound) => {
+                    self.status_msg = format!("Could not load data.csv: {}", e);
+                }
+                Err(e) => rejected.push(RejectedRow { line: 0, text: String::new(), error: e.to_string() }),
+            }
+            self.data_mtime = Self::data_file_mtime();
+            self.finish_load(rejected);
+        }
+
+        // Add series if none, the one shown while loading is replaced later
+        if self.data_series.is_empty() {
+            self.data_series.push(DataSeries::new());
+        }
+        self.find_duplicates();
+    }
+
+    // Adds series.toml to the points read and asks about rows that were not
+    fn finish_load(&mut self, rejected: Vec<RejectedRow>) {
+        if let Err(e) = Self::read_meta(&mut self.data_series, "series.toml".to_string()) {
+            self.status_msg = format!("Could not load series.toml: {}", e);
+        }
+        // What is on disk now, unless rows were dropped that a save should clear out
+        if rejected.is_empty() {
+            self.saved_points = Some(self.points_state());
+            self.saved_meta = self.meta_toml().ok().map(|content| text_hash(&content));
+        }
+        if !rejected.is_empty() {
+            self.recovery = Some(Recovery {
+                rejected,
+                points: self.data_series.iter().map(|s| s.data.len()).sum(),
+                choice: 0,
+                scroll: 0,
+                backup: None,
+                read_only: self.read_only,
+            });
+            self.read_only = true;
+        }
+    }
+
+    // Parses data.csv on a background thread, poll_loading takes the series in
+    fn load_in_background(&mut self) {
+        let (tx, rx) = mpsc::channel();
+        // Changes made while the file is read still count as changes
+        self.data_mtime = Self::data_file_mtime();
+        thread::spawn(move || {
+            let (loaded, rejected) = match fs::read("data.csv") {
+                Ok(content) => Self::parse_csv(&content, Some(&|p| {
+                    let _ = tx.send(LoadMsg::Progress(p));
+                })),
+                Err(e) => (Vec::new(), vec![RejectedRow { line: 0, text: String::new(), error: e.to_string() }]),
+            };
+            let _ = tx.send(LoadMsg::Done(loaded, rejected));
+        });
+        self.loading = Some(Loading { rx, progress: 0.0, read_only: self.read_only });
+        self.read_only = true;
+        self.status_msg = "Loading data.csv".to_string();
+    }
+
+    // True when there was news from loading data.csv
+    fn poll_loading(&mut self) -> bool {
+        let Some(loading) = &mut self.loading else { return false };
+        let mut done = None;
+        let mut news = false;
+        loop {
+            match loading.rx.try_recv() {
+                Ok(LoadMsg::Progress(p)) => loading.progress = p,
+                Ok(LoadMsg::Done(loaded, rejected)) => done = Some((loaded, rejected)),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    done = done.or_else(|| Some((Vec::new(), vec![RejectedRow { line: 0, text: String::new(), error: "loading stopped".to_string() }])));
+                    break;
+                }
+            }
+            news = true;
+        }
+        let Some((loaded, rejected)) = done else { return news };
+
+        self.read_only = loading.read_only;
+        self.loading = None;
+        self.data_series = loaded;
+        self.status_msg = "h: help".to_string();
+        self.finish_load(rejected);
+        if self.data_series.is_empty() {
+            self.data_series.push(DataSeries::new());
+        }
+        self.select_serie(0);
+        self.run_startup_commands();
+        true
+    }
+
+    // The leading : is optional, like typed after pressing :
+    fn run_startup_commands(&mut self) {
+        for command in std::mem::take(&mut self.startup_commands) {
+            self.run_command(command.trim_start().trim_start_matches(':'));
+        }
+        self.find_duplicates();
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        self.load(true);
+
+        // The API only runs with a token, from TRACKTUI_TOKEN or the config
+        if let Some(addr) = &self.serve {
+            let token = std::env::var("TRACKTUI_TOKEN").unwrap_or_else(|_| self.config.api_token.clone());
+            self.status_msg = match token.is_empty() {
+                true => "API not started: set TRACKTUI_TOKEN or api_token".to_string(),
+                false => match server::start(addr, token) {
+                    Ok(api) => {
+                        self.api = Some(api);
+                        format!("Serving the API on {}", addr)
+                    }
+                    Err(e) => format!("Could not serve on {}: {}", addr, e),
+                },
+            };
+        }
+
+        // Commands wait for the points when they are still loading
+        if self.loading.is_none() {
+            self.run_startup_commands();
+        }
+
+        if self.config.mouse {
+            set_mouse_capture(true);
+        }
+
+        // Main loop
+        self.last_save = Some(Instant::now());
+        while !self.exit {
+            let start = Instant::now();
+            terminal.draw(|frame| self.draw(frame))?;
+            self.draw_time = (self.draw_time * 3 + start.elapsed()) / 4;
+            self.last_draw = Some(Instant::now());
+            self.handle_events()?;
+            self.autosave();
+            if self.edit_pending {
+                self.edit_pending = false;
+                self.edit_externally(terminal)?;
+            }
+            if self.suspend_pending {
+                self.suspend_pending = false;
+                self.suspend(terminal)?;
+            }
+        }
+        set_mouse_capture(false);
+
+        // Nothing is written when the data was locked or generated
+        let save = !self.demo && !self.read_only;
+        let mut saved = save;
+        // Files that would come out the same are left alone
+        let mut hook_args = vec!["data.csv".to_string()];
+        let mut written = false;
+
+        // Write csv
+        if save {
+            match self.save_points() {
+                Ok(Some(changed)) => {
+                    written = true;
+                    hook_args.extend(changed);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    saved = false;
+                    self.status_msg = format!("Could not write to data.csv (Press any ket to exit): {}", e);
+                    terminal.draw(|frame| self.draw(frame))?;
+                    event::read()?;
+                }
+            }
+        }
+
+        // Write series metadata
+        if save {
+            match self.save_meta() {
+                Ok(wrote) => written |= wrote,
+                Err(e) => {
+                    saved = false;
+                    self.status_msg = format!("Could not write to series.toml (Press any key to exit): {}", e);
+                    terminal.draw(|frame| self.draw(frame))?;
+                    event::read()?;
+                }
+            }
+        }
+
+        if saved && written {
+            self.run_hook(self.config.hooks.on_save.clone(), &hook_args);
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        if self.aggregate_inputs != self.aggregate_inputs() {
+            self.refresh_aggregates();
+        }
+        match self.mode {
+            ViewMode::Graph => self.draw_graph_view(frame),
+            ViewMode::Menu => self.draw_menu_view(frame),
+            ViewMode::Series => self.draw_series_view(frame),
+            ViewMode::Trash => self.draw_trash_view(frame),
+            ViewMode::Table => self.draw_table_view(frame),
+            ViewMode::Spectrum => self.draw_spectrum_view(frame),
+            ViewMode::Help => self.draw_help_view(frame),
+            ViewMode::Settings => self.draw_settings_view(frame),
+            ViewMode::Compare => self.draw_compare_view(frame),
+            ViewMode::Batch => self.draw_batch_view(frame),
+        }
+
+        if self.merge_report.is_some() {
+            self.draw_merge_report(frame);
+        }
+
+        if self.pending_insert.is_some() {
+            self.draw_pending_insert(frame);
+        }
+
+        if self.doctor.is_some() {
+            self.draw_doctor(frame);
+        }
+
+        if self.duplicate.is_some() && self.merge_report.is_none() && self.recovery.is_none() {
+            self.draw_duplicate(frame);
+        }
+
+        if self.recovery.is_some() {
+            self.draw_recovery(frame);
+        }
+
+        if self.template_idx.is_some() {
+            self.draw_template_picker(frame);
+        }
+
+        if self.browser.is_some() {
+            self.draw_browser(frame);
+        }
+
+        if self.tour.is_some() {
+            self.draw_tour(frame);
+        }
+
+        if let Some(loading) = &self.loading {
+            let area = center(frame.area(), Constraint::Length(30), Constraint::Length(3));
+            frame.render_widget(Clear, area);
+            frame.render_widget(Paragraph::new(format!("Loading data.csv  {:.0}%", loading.progress * 100.0))
+                .alignment(Alignment::Center)
+                .block(Block::bordered()), area);
+        }
+
+        if let Some(command) = &self.command {
+            let [_, area] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+            frame.render_widget(Clear, area);
+            frame.render_widget(Paragraph::new(format!(":{}", command)), area);
+        }
+
+        if self.recording.is_some() {
+            let [area] = Layout::horizontal([Constraint::Length(7)]).flex(Flex::End).areas(frame.area());
+            let [area] = Layout::vertical([Constraint::Length(1)]).areas(area);
+            frame.render_widget(Paragraph::new(" ● REC".fg(self.theme.alert).bold()), area);
+        }
+    }
+
+    fn draw_template_picker(&self, frame: &mut Frame) {
+        let Some(idx) = self.template_idx else { return };
+        let templates = self.config.templates();
+
+        let mut lines: Vec<Line> = templates.iter().enumerate()
+            .map(|(i, t)| {
+                let unit = match t.unit.is_empty() {
+                    true => String::new(),
+                    false => format!("  ({})", t.unit),
+                };
+                match i == idx {
+                    true => Line::from(vec![t.name.clone().bold().reversed(), unit.dim()]),
+                    false => Line::from(vec![t.name.clone().into(), unit.dim()]),
+                }
+            })
+            .collect();
+        lines.push(Line::from(""));
+        lines.push(Line::from("ENTER: create  ESC: cancel".dim()));
+
+        let area = center(
+            frame.area(),
+            Constraint::Length(34),
+            Constraint::Length(lines.len() as u16 + 2),
+        );
+
+        let picker = Paragraph::new(Text::from(lines))
+            .block(Block::bordered()
+                .title(" New series from template ")
+                .title_alignment(Alignment::Center)
+                .padding(Padding::horizontal(1)));
+        frame.render_widget(Clear, area);
+        frame.render_widget(picker, area);
+    }
+
+    fn draw_browser(&self, frame: &mut Frame) {
+        let Some(browser) = &self.browser else { return };
+
+        let area = center(frame.area(), Constraint::Length(60), Constraint::Percentage(70));
+        let [list_area, filter_area] = Layout::vertical([Constraint::Min(3), Constraint::Length(3)]).areas(area);
+
+        // Keep the selected entry in view
+        let visible = browser.visible();
+        let height = list_area.height.saturating_sub(2) as usize;
+        let offset = (browser.idx + 1).saturating_sub(height);
+        let lines: Vec<Line> = visible.iter().enumerate().skip(offset).take(height)
+            .map(|(i, (name, dir))| {
+                let name = match dir {
+                    true => format!("{}/", name),
+                    false => name.clone(),
+                };
+                match (i == browser.idx, dir) {
+                    (true, _) => Line::from(name.bold().reversed()),
+                    (false, true) => Line::from(name.blue()),
+                    (false, false) => Line::from(name),
+                }
+            })
+            .collect();
+
+        let hint = match (visible.is_empty() && browser.action.saves() && !browser.filter.is_empty(), visible.get(browser.idx)) {
+            (true, _) => format!(" ENTER: {} as {} ", browser.action.label().to_lowercase(), browser.filter),
+            (false, Some((_, true))) => " ENTER: open  ESC: cancel ".to_string(),
+            (false, Some((_, false))) => format!(" ENTER: {}  ESC: cancel ", browser.action.label().to_lowercase()),
+            (false, None) => " ESC: cancel ".to_string(),
+        };
+        let list = Paragraph::new(Text::from(lines))
+            .block(Block::bordered()
+                .title(format!(" {} · {} ", browser.action.label(), browser.dir.display()))
+                .title_alignment(Alignment::Center)
+                .title_bottom(Line::from(hint.dim()).centered())
+                .padding(Padding::horizontal(1)));
+        let filter = Paragraph::new(format!("{}_", browser.filter))
+            .block(Block::bordered()
+                .title(match browser.action.saves() {
+                    true => " Filter or new file name ",
+                    false => " Filter ",
+                })
+                .padding(Padding::horizontal(1)));
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(list, list_area);
+        frame.render_widget(filter, filter_area);
+    }
+
+    fn draw_tour(&self, frame: &mut Frame) {
+        let Some(step) = self.tour else { return };
+
+        let [_, area] = Layout::vertical([Constraint::Min(0), Constraint::Length(6)]).areas(frame.area());
+        let area = center(area, Constraint::Length(64), Constraint::Length(5));
+
+        let tour = Paragraph::new(TOUR[step])
+            .wrap(Wrap { trim: true })
+            .block(Block::bordered()
+                .title(format!(" Tour {}/{} ", step + 1, TOUR.len()))
+                .title_alignment(Alignment::Center)
+                .title_bottom(Line::from(" F10: skip ").right_aligned())
+                .padding(Padding::horizontal(1))
+                .border_style(Style::default().fg(self.theme.accent)));
+        frame.render_widget(Clear, area);
+        frame.render_widget(tour, area);
+    }
+
+    // Moves the tour on once the current step's action has been performed
+    fn advance_tour(&mut self) {
+        let Some(step) = self.tour else { return };
+
+        let points = self.data_series[self.selected_serie].data.len();
+        let done = match step {
+            1 => matches!(self.input_mode, InputMode::Insert),
+            2 => points > self.tour_points,
+            3 => matches!(self.mode, ViewMode::Table),
+            4 => self.table_state.selected().is_some(),
+            5 => points < self.tour_points,
+            6 => matches!(self.mode, ViewMode::Spectrum),
+            _ => false,
+        };
+
+        if done {
+            self.tour = Some(step + 1);
+        }
+        self.tour_points = points;
+    }
+
+    fn draw_merge_report(&self, frame: &mut Frame) {
+        let Some(report) = &self.merge_report else { return };
+
+        let mut lines = Vec::new();
+        for name in &report.added {
+            lines.push(Line::from(vec!["+ ".fg(self.theme.on_track).bold(), name.clone().into(), "  new series".dim()]));
+        }
+        for (name, added, removed) in &report.updated {
+            lines.push(Line::from(vec![
+                "~ ".fg(self.theme.accent).bold(),
+                name.clone().into(),
+                format!("  +{} -{} points", added, removed).dim(),
+            ]));
+        }
+        for name in &report.missing {
+            lines.push(Line::from(vec!["? ".fg(self.theme.alert).bold(), name.clone().into(), "  not in file, kept".dim()]));
+        }
+        if lines.is_empty() {
+            lines.push(Line::from("No changes"));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(match report.pending {
+            Some(_) => "ENTER: apply  ESC: keep mine".dim(),
+            None => "ENTER: close".dim(),
+        }));
+
+        let area = center(
+            frame.area(),
+            Constraint::Length(40),
+            Constraint::Length(lines.len() as u16 + 2),
+        );
+
+        let report = Paragraph::new(Text::from(lines))
+            .block(Block::bordered()
+                .title(format!(" {} ", report.source))
+                .title_alignment(Alignment::Center)
+                .padding(Padding::horizontal(1)));
+        frame.render_widget(Clear, area);
+        frame.render_widget(report, area);
+    }
+
+    fn draw_duplicate(&self, frame: &mut Frame) {
+        let Some((i, j)) = self.duplicate else { return };
+        let (a, b) = (&self.data_series[i], &self.data_series[j]);
+
+        let mut choices = Vec::new();
+        for (k, &label) in DUPLICATE_CHOICES.iter().enumerate() {
+            if k > 0 {
+                choices.push("  ".into());
+            }
+            choices.push(match k == self.duplicate_idx {
+                true => label.bold().reversed(),
+                false => label.into(),
+            });
+        }
+        let lines = vec![
+            Line::from(format!("Two series are named {}, with {} and {} points.", a.name, a.data.len(), b.data.len())),
+            Line::from("data.csv cannot tell them apart, they would be joined on save.".dim()),
+            Line::from(""),
+            Line::from(choices).centered(),
+        ];
+
+        let area = center(
+            frame.area(),
+            Constraint::Length(68),
+            Constraint::Length(lines.len() as u16 + 2),
+        );
+        let dialog = Paragraph::new(Text::from(lines))
+            .wrap(Wrap { trim: true })
+            .block(Block::bordered()
+                .title(" Duplicate series name ")
+                .title_alignment(Alignment::Center)
+                .padding(Padding::horizontal(1))
+                .border_style(Style::default().fg(self.theme.accent)));
+        frame.render_widget(Clear, area);
+        frame.render_widget(dialog, area);
+    }
+
+    fn draw_doctor(&self, frame: &mut Frame) {
+        let Some(findings) = &self.doctor else { return };
+
+        let area = center(frame.area(), Constraint::Length(76), Constraint::Percentage(70));
+        // Two lines per finding, keep the selected one in view
+        let visible = (area.height.saturating_sub(2) / 2).max(1) as usize;
+        let offset = (self.doctor_idx + 1).saturating_sub(visible);
+        let mut lines = Vec::new();
+        for (i, finding) in findings.iter().enumerate().skip(offset).take(visible) {
+            let mut issue = Line::from(vec![
+                "● ".fg(self.serie_color(finding.serie)),
+                format!("{}  ", self.data_series[finding.serie].name).bold(),
+                finding.issue.clone().into(),
+            ]);
+            if i == self.doctor_idx {
+                issue = issue.reversed();
+            }
+            lines.push(issue);
+            lines.push(Line::from(format!("  {}", finding.hint).dim()));
+        }
+
+        let report = Paragraph::new(Text::from(lines))
+            .block(Block::bordered()
+                .title(format!(" Doctor · {} issues ", findings.len()))
+                .title_alignment(Alignment::Center)
+                .title_bottom(Line::from(" ⇅: select  ENTER: open series  ESC: close ".dim()).centered())
+                .padding(Padding::horizontal(1)));
+        frame.render_widget(Clear, area);
+        frame.render_widget(report, area);
+    }
+
+    fn draw_recovery(&self, frame: &mut Frame) {
+        let Some(recovery) = &self.recovery else { return };
+
+        let mut choices = Vec::new();
+        for (k, &label) in RECOVERY_CHOICES.iter().enumerate() {
+            if k > 0 {
+                choices.push("  ".into());
+            }
+            choices.push(match k == recovery.choice {
+                true => label.bold().reversed(),
+                false => label.into(),
+            });
+        }
+        let hint = match RECOVERY_CHOICES[recovery.choice] {
+            "Keep good rows" => "Go on with what was read, the bad rows are moved to data.csv.rejected".to_string(),
+            "Back up" => match &recovery.backup {
+                Some(path) => format!("Backed up to {}", path),
+                None => "Copy data.csv as it is now before deciding".to_string(),
+            },
+            "Start fresh" => "Back up data.csv and start with an empty series".to_string(),
+            _ => "Leave without writing anything".to_string(),
+        };
+
+        let area = center(frame.area(), Constraint::Percentage(80), Constraint::Percentage(80));
+        let [summary, list, _, buttons, help] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ]).areas(area.inner(Margin::new(2, 1)));
+
+        let series = self.data_series.iter().filter(|s| !s.data.is_empty()).count();
+        let summary_text = Text::from(vec![
+            Line::from(format!("Read {} points in {} series.", recovery.points, series)),
+            Line::from(format!("{} rows could not be read:", recovery.rejected.len()).fg(self.theme.alert)),
+        ]);
+
+        let rows: Vec<Line> = recovery.rejected.iter()
+            .skip(recovery.scroll)
+            .take(list.height as usize)
+            .map(|row| match row.line {
+                0 => Line::from(row.error.clone().fg(self.theme.alert)),
+                line => Line::from(vec![
+                    format!("line {:>6}  ", line).dim(),
+                    row.text.clone().into(),
+                    format!("  {}", row.error).fg(self.theme.alert),
+                ]),
+            })
+            .collect();
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(Block::bordered()
+            .title(" data.csv is damaged ")
+            .title_alignment(Alignment::Center)
+            .border_style(Style::default().fg(self.theme.alert)), area);
+        frame.render_widget(Paragraph::new(summary_text), summary);
+        frame.render_widget(Paragraph::new(Text::from(rows)), list);
+        frame.render_widget(Paragraph::new(Line::from(choices)).centered(), buttons);
+        frame.render_widget(Paragraph::new(Line::from(hint.dim())).centered(), help);
+    }
+
+    fn draw_pending_insert(&self, frame: &mut Frame) {
+        let Some(pending) = &self.pending_insert else { return };
+
+        let mut lines: Vec<Line> = pending.violations.iter().take(8)
+            .map(|v| Line::from(vec!["! ".fg(self.theme.alert).bold(), v.clone().into()]))
+            .collect();
+        if pending.violations.len() > 8 {
+            lines.push(Line::from(format!("and {} more", pending.violations.len() - 8).dim()));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from("ENTER: insert anyway  ESC: cancel".dim()));
+
+        let area = center(
+            frame.area(),
+            Constraint::Length(52),
+            Constraint::Length(lines.len() as u16 + 2),
+        );
+
+        let dialog = Paragraph::new(Text::from(lines))
+            .block(Block::bordered()
+                .title(" Check before inserting ")
+                .title_alignment(Alignment::Center)
+                .padding(Padding::horizontal(1))
+                .border_style(Style::default().fg(self.theme.alert)));
+        frame.render_widget(Clear, area);
+        frame.render_widget(dialog, area);
+    }
+
+    fn draw_menu_view(&self, frame: &mut Frame) {
+        let lines = vec![
+            Line::from(vec!["h".bold(), "   Help".into()]),
+            Line::from(vec!["g".bold(), "   Graph".into()]),
+            Line::from(vec!["t".bold(), "   Table".into()]),
+            Line::from(vec!["f".bold(), "   Spectrum".into()]),
+            Line::from(vec!["c".bold(), "   Compare".into()]),
+            Line::from(vec!["s".bold(), "   Settings".into()]),
+            Line::from(vec!["S".bold(), "   Series".into()]),
+            Line::from(vec!["x".bold(), "   Trash".into()]),
+            Line::from(vec!["q".bold(), "   Quit".into()]),
+        ];
+
+        let area = center(
+            frame.area(),
+            Constraint::Length(14),
+            Constraint::Length(lines.len() as u16),
+        );
+
+        let text = Text::from(lines);
+        let menu = Paragraph::new(text).alignment(Alignment::Center);
+        frame.render_widget(Clear, area);
+        frame.render_widget(menu, area);
+
+        // Series nothing was logged to for longer than their cadence
+        let overdue: Vec<&str> = self.data_series.iter()
+            .filter(|s| !s.archived && s.overdue())
+            .map(|s| s.name.as_str())
+            .collect();
+        if !overdue.is_empty() {
+            let [_, below] = Layout::vertical([Constraint::Length(area.bottom() + 1), Constraint::Length(1)])
+                .areas(frame.area());
+            let line = Line::from(vec!["Overdue: ".fg(self.theme.accent).bold(), overdue.join(", ").fg(self.theme.accent)]);
+            frame.render_widget(Paragraph::new(line).centered(), below);
+        }
+    }
+
+    // Every series with its point count, grouped into collapsible sections
+    fn draw_series_view(&self, frame: &mut Frame) {
+        let favorites = self.favorites();
+        let rows: Vec<Row> = self.series_list().iter().enumerate()
+            .map(|(i, entry)| {
+                let row = match entry {
+                    ListEntry::Group(group) => {
+                        let count = self.data_series.iter().filter(|s| &s.group == group).count();
+                        let arrow = if self.collapsed.contains(group) { "▸" } else { "▾" };
+                        Row::new(vec![
+                            Cell::from(format!("{} {}", arrow, group).bold()),
+                            Cell::from(Line::from(format!("{} series", count).dim()).right_aligned()),
+                        ])
+                    }
+                    ListEntry::Serie(s) => {
+                        let serie = &self.data_series[*s];
+                        let indent = if serie.group.is_empty() { "" } else { "  " };
+                        // Only the arrow tells the trend, the name keeps the selection's color
+                        let (arrow, color) = serie.trend_arrow(&self.theme);
+                        let mut name = Line::from(vec![
+                            format!("{}● ", indent).fg(self.serie_color(*s)),
+                            match *s == self.selected_serie {
+                                true => serie.name.clone().fg(self.serie_color(*s)),
+                                false => serie.name.clone().into(),
+                            },
+                            arrow.fg(color),
+                        ]);
+                        if let Some(n) = favorites.iter().position(|&f| f == *s) {
+                            name.push_span(match n < 9 {
+                                true => format!(" ★{}", n + 1),
+                                false => " ★".to_string(),
+                            }.fg(self.theme.accent));
+                        }
+                        if self.overlay.contains(&serie.name) {
+                            name.push_span(" overlaid".fg(self.serie_color(*s)));
+                        }
+                        if serie.archived {
+                            name.push_span(" archived".fg(self.theme.muted));
+                        } else if serie.overdue() {
+                            name.push_span(" overdue".fg(self.theme.accent));
+                        }
+                        let row = Row::new(vec![
+                            Cell::from(name),
+                            Cell::from(Line::from(serie.data.len().to_string()).right_aligned()),
+                        ]);
+                        match *s == self.selected_serie {
+                            true => row.bold(),
+                            false => row,
+                        }
+                    }
+                };
+                match i == self.series_idx {
+                    true => row.reversed(),
+                    false => row,
+                }
+            })
+            .collect();
+
+        let area = center(
+            frame.area(),
+            Constraint::Length(46),
+            Constraint::Percentage(70),
+        );
+        let table = Table::new(rows, [Constraint::Min(20), Constraint::Length(10)])
+            .header(Row::new(vec![Cell::from("Name"), Cell::from(Line::from("Points").right_aligned())]).bold())
+            .block(Block::bordered()
+                .title(match (&self.tag_filter, self.show_archived) {
+                    (Some(tag), true) => format!(" Series · {} · with archived ", tag),
+                    (Some(tag), false) => format!(" Series · {} ", tag),
+                    (None, true) => " Series · with archived ".to_string(),
+                    (None, false) => " Series ".to_string(),
+                })
+                .title_alignment(Alignment::Center)
+                .title_bottom(Line::from(format!(" {} ", self.status_msg)).centered())
+                .padding(Padding::horizontal(1)));
+        let mut state = TableState::default().with_selected(Some(self.series_idx));
+        let [list, details] = Layout::vertical([Constraint::Min(5), Constraint::Length(8)]).areas(area);
+        frame.render_stateful_widget(table, list, &mut state);
+        self.draw_series_details(frame, details);
+
+        if self.confirm_delete {
+            let serie = &self.data_series[self.selected_serie];
+            let text = Text::from(vec![
+                Line::from(format!("Delete {} and its {} points?", serie.name, serie.data.len()).bold()),
+                Line::from("ENTER: delete  ESC: cancel".dim()),
+            ]);
+            let popup = center(area, Constraint::Length(44), Constraint::Length(4));
+            frame.render_widget(Clear, popup);
+            frame.render_widget(Paragraph::new(text).centered().block(Block::bordered()), popup);
+        }
+
+        if self.confirm_clear {
+            let serie = &self.data_series[self.selected_serie];
+            let text = Text::from(vec![
+                Line::from(format!("Clear all {} points of {}?", serie.data.len(), serie.name).bold()),
+                Line::from("They are moved to the trash".dim()),
+                Line::from("ENTER: clear  ESC: cancel".dim()),
+            ]);
+            let popup = center(area, Constraint::Length(44), Constraint::Length(5));
+            frame.render_widget(Clear, popup);
+            frame.render_widget(Paragraph::new(text).centered().block(Block::bordered()), popup);
+        }
+
+        if let Some(query) = &self.series_search {
+            self.draw_series_search(frame, area, query);
+        }
+
+        let [_, hint] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        frame.render_widget(Paragraph::new("⇅: select  J/K: move  ENTER: open  /: find  r: rename  #: tags  F: filter  d: delete  ESC: menu".dim()).centered(), hint);
+    }
+
+    fn draw_series_search(&self, frame: &mut Frame, area: Rect, query: &str) {
+        let area = center(area, Constraint::Length(40), Constraint::Percentage(60));
+        let [input_area, list_area] = Layout::vertical([Constraint::Length(3), Constraint::Min(3)]).areas(area);
+
+        // Keep the selected match in view
+        let results = self.search_results();
+        let height = list_area.height.saturating_sub(2) as usize;
+        let offset = (self.search_idx + 1).saturating_sub(height);
+        let lines: Vec<Line> = results.iter().enumerate().skip(offset).take(height)
+            .map(|(i, &s)| {
+                let serie = &self.data_series[s];
+                let mut line = Line::from(vec![
+                    "● ".fg(self.serie_color(s)),
+                    serie.name.clone().into(),
+                ]);
+                if !serie.group.is_empty() {
+                    line.push_span(format!("  {}", serie.group).dim());
+                }
+                match i == self.search_idx {
+                    true => line.reversed(),
+                    false => line,
+                }
+            })
+            .collect();
+
+        let input = Paragraph::new(format!("{}_", query))
+            .block(Block::bordered()
+                .title(" Find series ")
+                .title_alignment(Alignment::Center)
+                .padding(Padding::horizontal(1)));
+        let list = Paragraph::new(Text::from(lines))
+            .block(Block::bordered()
+                .title_bottom(Line::from(format!(" {} of {} · ENTER: open  ESC: cancel ", results.len(), self.data_series.len()).dim()).centered())
+                .padding(Padding::horizontal(1)));
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(input, input_area);
+        frame.render_widget(list, list_area);
+    }
+
+    // Metadata of the highlighted series under the series list
+    fn draw_series_details(&self, frame: &mut Frame, area: Rect) {
+        let Some(&ListEntry::Serie(s)) = self.series_list().get(self.series_idx) else {
+            frame.render_widget(Block::bordered(), area);
+            return;
+        };
+        let serie = &self.data_series[s];
+        let field = |name: &str, value: &str| Line::from(vec![format!("{:<9}", name).bold(), value.to_string().into()]);
+        let or_dash = |v: &str| if v.is_empty() { "-".to_string() } else { v.to_string() };
+
+        let lines = vec![
+            match serie.description.is_empty() {
+                true => Line::from("No description (s: settings)".dim()),
+                false => Line::from(serie.description.clone()),
+            },
+            field("Unit", &or_dash(&serie.unit)),
+            field("Created", &or_dash(&serie.created_at)),
+            field("Cadence", &or_dash(serie.cadence.as_deref().unwrap_or_default())),
+            field("Tags", &or_dash(&serie.tags.join(", "))),
+        ];
+        let details = Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .block(Block::bordered().padding(Padding::horizontal(1)));
+        frame.render_widget(details, area);
+    }
+
+    // Deleted points of the selected series, the latest deletion first
+    fn draw_trash_view(&self, frame: &mut Frame) {
+        let serie = &self.data_series[self.selected_serie];
+        let precision = serie.precision(self.config.precision);
+        let rows: Vec<Row> = serie.trash.iter().rev().enumerate()
+            .map(|(i, p)| {
+                let row = Row::new(vec![serie.describe_x(p.x, precision), format_value(p.y, precision)]);
+                match i == self.trash_idx {
+                    true => row.reversed(),
+                    false => row,
+                }
+            })
+            .collect();
+
+        let area = center(
+            frame.area(),
+            Constraint::Length(40),
+            Constraint::Percentage(70),
+        );
+        let title = match serie.trash.is_empty() {
+            true => format!(" Trash · {} · empty ", serie.name),
+            false => format!(" Trash · {} ", serie.name),
+        };
+        let table = Table::new(rows, [Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+            .header(Row::new(vec!["X", serie.y_name()]).bold())
+            .block(Block::bordered()
+                .title(title)
+                .title_alignment(Alignment::Center)
+                .title_bottom(Line::from(format!(" {} ", self.status_msg)).centered())
+                .padding(Padding::horizontal(1)));
+        frame.render_widget(table, area);
+
+        if self.confirm_empty {
+            let text = Text::from(vec![
+                Line::from(format!("Delete the {} points in the trash for good?", serie.trash.len()).bold()),
+                Line::from("They cannot be restored".dim()),
+                Line::from("ENTER: delete  ESC: cancel".dim()),
+            ]);
+            let popup = center(frame.area(), Constraint::Length(48), Constraint::Length(5));
+            frame.render_widget(Clear, popup);
+            frame.render_widget(Paragraph::new(text).centered().block(Block::bordered()), popup);
+        }
+
+        let [_, hint] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        frame.render_widget(Paragraph::new("j/k: select  ENTER: restore  E: empty trash  ESC: table".dim()).centered(), hint);
+    }
+
+    // Series matching the / query, best first. Searches every series,
+    // also ones in folded groups or without the filter tag, but not archived ones
+    fn search_results(&self) -> Vec<usize> {
+        let query = self.series_search.as_deref().unwrap_or_default();
+        let mut results: Vec<(i32, usize)> = self.data_series.iter().enumerate()
+            .filter(|(_, s)| self.show_archived || !s.archived)
+            .filter_map(|(i, s)| fuzzy_score(query, &s.name).map(|score| (score, i)))
+            .collect();
+        results.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        results.into_iter().map(|(_, i)| i).collect()
+    }
+
+    // Favorites first, then ungrouped series, then each group in order of first appearance
+    // Only series with the filter tag are listed, and groups holding one.
+    // Archived series only while show_archived is set
+    fn series_list(&self) -> Vec<ListEntry> {
+        let listed = |s: &DataSeries| self.tag_filter.as_ref().is_none_or(|tag| s.tags.contains(tag))
+            && (self.show_archived || !s.archived);
+        let mut entries: Vec<ListEntry> = self.data_series.iter().enumerate()
+            .filter(|(_, s)| s.favorite && listed(s))
+            .chain(self.data_series.iter().enumerate().filter(|(_, s)| !s.favorite && s.group.is_empty() && listed(s)))
+            .map(|(i, _)| ListEntry::Serie(i))
+            .collect();
+        let listed = |s: &DataSeries| !s.favorite && listed(s);
+
+        let mut groups: Vec<&str> = Vec::new();
+        for serie in self.data_series.iter().filter(|s| listed(s)) {
+            if !serie.group.is_empty() && !groups.contains(&serie.group.as_str()) {
+                groups.push(&serie.group);
+            }
+        }
+        for group in groups {
+            entries.push(ListEntry::Group(group.to_string()));
+            if !self.collapsed.iter().any(|c| c == group) {
+                entries.extend(self.data_series.iter().enumerate()
+                    .filter(|(_, s)| s.group == group && listed(s))
+                    .map(|(i, _)| ListEntry::Serie(i)));
+            }
+        }
+        entries
+    }
+
+    // Steps the series list filter through every tag in use, then back to none
+    fn cycle_tag_filter(&mut self) {
+        let mut tags: Vec<&String> = self.data_series.iter().flat_map(|s| &s.tags).collect();
+        tags.sort();
+        tags.dedup();
+        let next = match &self.tag_filter {
+            Some(tag) => tags.iter().position(|t| *t == tag).and_then(|i| tags.get(i + 1)),
+            None => tags.first(),
+        };
+        self.tag_filter = next.map(|t| t.to_string());
+        self.series_idx = 0;
+        self.status_msg = match &self.tag_filter {
+            Some(tag) => format!("Tagged {}", tag),
+            None if tags.is_empty() => "No tags yet (#: edit tags)".to_string(),
+            None => "All series".to_string(),
+        };
+    }
+
+    // tags <tag>, <tag>, ... replaces the tags of the selected series
+    fn set_tags(&mut self, arg: &str) {
+        let serie = &mut self.data_series[self.selected_serie];
+        serie.tags.clear();
+        for tag in arg.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            if !serie.tags.iter().any(|t| t == tag) {
+                serie.tags.push(tag.to_string());
+            }
+        }
+        self.status_msg = match serie.tags.is_empty() {
+            true => format!("{} has no tags", serie.name),
+            false => format!("{} tagged {}", serie.name, serie.tags.join(", ")),
+        };
+    }
+
+    fn draw_help_view(&mut self, frame: &mut Frame) {
+        let mut lines = Vec::new();
+        for (i, (section, keys)) in KEYMAP.iter().enumerate() {
+            if i > 0 {
+                lines.push(Line::from(""));
+            }
+            lines.push(Line::from(section.bold().underlined()));
+            lines.push(Line::from(""));
+            for &(key, action) in keys.iter() {
+                lines.push(match *section {
+                    "Commands" => Line::from(format!(":{}", key).dim()),
+                    _ => Line::from(vec![key.bold(), format!("   {}", action).into()]),
+                });
+            }
+        }
+
+        // Scrolled by ⇅ once it is taller than the terminal
+        let overflow = (lines.len() as u16 + 2).saturating_sub(frame.area().height);
+        self.help_scroll = self.help_scroll.min(overflow);
+
+        let area = center(
+            frame.area(),
+            Constraint::Length(44),
+            Constraint::Length(lines.len() as u16 + 2),
+        );
+
+        let text = Text::from(lines);
+        let help = Paragraph::new(text).alignment(Alignment::Center).scroll((self.help_scroll, 0));
+        frame.render_widget(help, area);
+    }
+
+    fn draw_settings_view(&self, frame: &mut Frame) {
+        let serie = &self.data_series[self.selected_serie];
+
+        let mut lines = Vec::new();
+        for (i, &setting) in SETTINGS.iter().enumerate() {
+            if setting == Setting::Unit {
+                lines.push(Line::from(""));
+                lines.push(Line::from(format!("Series {}", serie.name).bold().underlined()));
+            }
+
+            let value = match (&self.settings_edit, i == self.settings_idx) {
+                (Some(text), true) => format!("{}_", text),
+                _ => self.setting_value(setting),
+            };
+            let label = format!("{:<16}", self.setting_label(setting));
+            lines.push(match i == self.settings_idx {
+                true => Line::from(vec![label.bold().reversed(), " ".into(), value.bold()]),
+                false => Line::from(vec![label.into(), " ".into(), value.into()]),
+            });
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from("⇅: select  ⇆: change  ENTER: edit".dim()));
+
+        let area = center(
+            frame.area(),
+            Constraint::Length(46),
+            Constraint::Length(lines.len() as u16 + 4),
+        );
+
+        let settings = Paragraph::new(Text::from(lines))
+            .block(Block::bordered()
+                .title(format!(" Settings · {} ", CONFIG_PATH))
+                .title_alignment(Alignment::Center)
+                .title_bottom(Line::from(format!(" {} ", self.status_msg)).centered())
+                .padding(Padding::uniform(1)));
+        frame.render_widget(settings, area);
+    }
+
+    fn setting_label(&self, setting: Setting) -> &'static str {
+        match setting {
+            Setting::RefreshRate => "Refresh rate",
+            Setting::TableOrder => "Table order",
+            Setting::Precision => "Precision",
+            Setting::NudgeStep => "Nudge step",
+            Setting::TrendCheck => "Trend check",
+            Setting::Autosave => "Autosave",
+            Setting::DefaultView => "Default view",
+            Setting::Marker => "Marker",
+            Setting::Theme => "Theme",
+            Setting::Animations => "Animations",
+            Setting::Mouse => "Mouse",
+            Setting::Kpis => "KPIs",
+            Setting::Unit => "Unit",
+            Setting::Decimals => "Decimals",
+            Setting::InputStep => "Input step",
+            Setting::Description => "Description",
+            Setting::UnitScale => "Unit scaling",
+            Setting::Goal => "Goal",
+            Setting::Axis => "Y axis",
+            Setting::Labels => "Y labels",
+            Setting::SeriesMarker => "Marker",
+            Setting::Prefill => "Prefill",
+        }
+    }
+
+    fn setting_value(&self, setting: Setting) -> String {
+        let serie = &self.data_series[self.selected_serie];
+        match setting {
+            Setting::RefreshRate => format!("{} fps", self.config.refresh_rate),
+            Setting::TableOrder => self.config.table_order.label().trim().to_string(),
+            Setting::Precision => format!("{} decimals", self.config.precision),
+            Setting::NudgeStep => format!("±{}", self.config.nudge_step),
+            Setting::TrendCheck => match self.config.trend_check {
+                true => "on".to_string(),
+                false => "off".to_string(),
+            },
+            Setting::Autosave => match self.config.autosave {
+                0 => "off".to_string(),
+                minutes => format!("every {} min", minutes),
+            },
+            Setting::DefaultView => self.config.default_view.label().to_string(),
+            Setting::Marker => match self.marker() == self.config.marker.symbol() {
+                true => self.config.marker.label().to_string(),
+                false => format!("{} (dot, no UTF-8 locale)", self.config.marker.label()),
+            },
+            Setting::Theme => match self.config.theme.is_empty() {
+                true => "default".to_string(),
+                false => self.config.theme.clone(),
+            },
+            Setting::Animations => match self.config.animations {
+                true => "on".to_string(),
+                false => "off".to_string(),
+            },
+            Setting::Mouse => match self.config.mouse {
+                true => "on".to_string(),
+                false => "off".to_string(),
+            },
+            Setting::Kpis => match self.config.kpis.is_empty() {
+                true => format!("none (→: add {})", serie.name),
+                false => self.config.kpis.join(", "),
+            },
+            Setting::Unit => serie.unit.clone(),
+            Setting::Decimals => match serie.precision {
+                Some(p) => p.to_string(),
+                None => format!("as app ({})", self.config.precision),
+            },
+            Setting::InputStep => match serie.input_step {
+                Some(step) => format!("{} {}", step, serie.unit).trim_end().to_string(),
+                None => "as typed".to_string(),
+            },
+            Setting::Description => serie.description.clone(),
+            Setting::UnitScale => match serie.fixed_unit {
+                true => "off".to_string(),
+                false => match serie.y_unit() {
+                    (unit, factor) if factor > 1.0 => format!("auto, now {}", unit),
+                    _ => "auto".to_string(),
+                },
+            },
+            Setting::Goal => serie.goal.map_or("none".to_string(), |g| g.to_string()),
+            Setting::Axis => match serie.y_axis {
+                YAxis::Zero => "from zero".to_string(),
+                YAxis::Fit => "fit data".to_string(),
+                YAxis::Log => "log scale".to_string(),
+            },
+            Setting::Labels => serie.label_format.label().to_string(),
+            Setting::SeriesMarker => match serie.marker {
+                Some(marker) => marker.label().to_string(),
+                None => format!("as app ({})", self.config.marker.label()),
+            },
+            Setting::Prefill => serie.prefill.label().to_string(),
+        }
+    }
+
+    fn draw_table_view(&mut self, frame: &mut Frame) {
+        let serie = &self.data_series[self.selected_serie];
+        let width = match self.table_all {
+            true => 40,
+            false => {
+                let n_columns = 2 + serie.extra_columns().len() + serie.has_errors() as usize;
+                (n_columns as u16 * 8 + 6).max(20)
+            }
+        };
+        // Room for the delete choices
+        let width = match self.confirm_delete {
+            true => width.max(32),
+            false => width,
+        };
+        let area = center(
+            frame.area(),
+            Constraint::Length(width),
+            Constraint::Percentage(50),
+        );
+
+        let chunks = Layout::vertical(vec![
+            Constraint::Min(5),
+            Constraint::Length(4),
+        ]).split(area);
+
+        match self.table_all {
+            true => self.draw_all_table(frame, chunks[0]),
+            false => self.draw_table(frame, chunks[0]),
+        }
+
+        match self.confirm_delete {
+            true => {
+                let mut choices = Vec::new();
+                for (i, &(label, _)) in DELETE_CHOICES.iter().enumerate() {
+                    if i > 0 {
+                        choices.push("  ".into());
+                    }
+                    choices.push(match i == self.confirm_idx {
+                        true => label.bold().reversed(),
+                        false => label.into(),
+                    });
+                }
+                let text = Text::from(vec![
+                    Line::from(vec!["Delete?".bold()]),
+                    Line::from(choices),
+                ]);
+                let content = Paragraph::new(text).centered();
+                frame.render_widget(content, chunks[1]);
+            }
+            false => {
+                let content = match self.read_only {
+                    true => Paragraph::new("read-only  h: help").centered(),
+                    false => Paragraph::new("h: help").centered(),
+                };
+                frame.render_widget(content, chunks[1]);
+            }
+        }
+
+        if self.point_stats {
+            self.draw_point_stats(frame);
+        }
+    }
+
+    // Where the selected point stands within its series
+    fn draw_point_stats(&self, frame: &mut Frame) {
+        let Some((s, i)) = self.table_state.selected().and_then(|row| self.table_point(row)) else { return };
+        let serie = &self.data_series[s];
+        let (x, y) = serie.data[i];
+        let n = serie.data.len();
+        let precision = serie.precision(self.config.precision);
+
+        let rank = 1 + serie.data.iter().filter(|&&(_, py)| py > y).count();
+        let percentile = 100.0 * serie.data.iter().filter(|&&(_, py)| py <= y).count() as f64 / n as f64;
+        let mean = serie.data.iter().map(|&(_, py)| py).sum::<f64>() / n as f64;
+        let std = (serie.data.iter().map(|&(_, py)| (py - mean).powi(2)).sum::<f64>() / n as f64).sqrt();
+        let signed = |v: f64| match v >= 0.0 {
+            true => format!("+{}", format_value(v, precision)),
+            false => format_value(v, precision),
+        };
+
+        let delta = match i {
+            0 => "first point".to_string(),
+            _ => signed(y - serie.data[i - 1].1),
+        };
+        let from_mean = match std > 0.0 {
+            true => format!("{} ({:+.1}σ)", signed(y - mean), (y - mean) / std),
+            false => signed(y - mean),
+        };
+        let lines = vec![
+            Line::from(vec!["Rank        ".bold(), format!("{} of {}", rank, n).into()]),
+            Line::from(vec!["Percentile  ".bold(), format!("{:.0}%", percentile).into()]),
+            Line::from(vec!["vs previous ".bold(), delta.into()]),
+            Line::from(vec!["vs mean     ".bold(), from_mean.into()]),
+        ];
+
+        let area = center(frame.area(), Constraint::Length(36), Constraint::Length(lines.len() as u16 + 2));
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered()
+                .title(format!(" x = {}, y = {} ", serie.format_x(x, precision), format_value(y, precision)))
+                .title_alignment(Alignment::Center)
+                .padding(Padding::horizontal(1))),
+            area,
+        );
+    }
+
+    fn draw_table(&mut self, frame: &mut Frame, area: Rect) {
+        let serie = &self.data_series[self.selected_serie];
+        let has_errors = serie.has_errors();
+
+        let mut columns = vec!["X".to_string(), serie.y_name().to_string()];
+        columns.extend(serie.extra_columns().iter().cloned());
+        if has_errors {
+            columns.push("±".to_string());
+        }
+        let n_columns = columns.len();
+
+        let header = Row::new(columns)
+            .style(Style::default()
+                .fg(self.theme.accent)
+                .add_modifier(Modifier::BOLD))
+            .bottom_margin(1);
+
+        let rows: Vec<Row> = self.table_rows()
+            .into_iter()
+            .map(|(_, i)| {
+                let (x, y) = serie.data[i];
+                let precision = serie.precision(self.config.precision);
+                let x = match serie.pinned(i) {
+                    true => format!("★ {}", serie.format_x(x, precision)),
+                    false => serie.format_x(x, precision),
+                };
+                let mut cells = vec![Cell::from(x), Cell::from(format_value(y, precision))];
+                for j in 0..serie.extra_columns().len() {
+                    cells.push(Cell::from(serie.values(i).get(j).map_or(String::new(), |&v| format_value(v, precision))));
+                }
+                if has_errors {
+                    cells.push(Cell::from(serie.error(i).map_or(String::new(), |e| format_value(e, precision))));
+                }
+                Row::new(cells)
+            })
+            .collect();
+        let n_rows = rows.len();
+
+        let widths = vec![Constraint::Ratio(1, n_columns as u32); n_columns];
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(Block::bordered()
+                .title("  Table ⇅ ")
+                .title_bottom(Line::from(self.table_order.label()).centered())
+                .title_alignment(Alignment::Center)
+                .padding(Padding::uniform(2)))
+            .column_spacing(1)
+            .row_highlight_style(
+                Style::default()
+                .bg(self.theme.selected_bg)
+                .fg(self.theme.selected_fg)
+                .add_modifier(Modifier::BOLD)
+            );
+
+        // Inside the border, padding and header
+        let mut state = self.ease_table(area.height.saturating_sub(8) as usize, n_rows);
+        frame.render_stateful_widget(table, area, &mut state);
+    }
+
+    // State to draw the table with. Scrolling eases the rows from the offset
+    // last drawn, the selection is only highlighted once it is in view
+    fn ease_table(&mut self, height: usize, n_rows: usize) -> TableState {
+        // Keep the selection in view, like the table widget would
+        let mut offset = self.table_state.offset().min(n_rows.saturating_sub(1));
+        if let Some(row) = self.table_state.selected() {
+            offset = offset.min(row).max((row + 1).saturating_sub(height.max(1)));
+        }
+        if offset != self.table_state.offset() && self.config.animations {
+            self.table_anim = Some((self.table_shown, Instant::now()));
+        }
+        *self.table_state.offset_mut() = offset;
+
+        let shown = match self.table_anim.and_then(|(from, start)| Some((from, ease(start)?))) {
+            Some((from, t)) => from + (offset as f64 - from) * t,
+            None => {
+                self.table_anim = None;
+                offset as f64
+            }
+        };
+        self.table_shown = shown;
+        let first = shown.round() as usize;
+        TableState::default()
+            .with_offset(first)
+            .with_selected(self.table_state.selected().filter(|&row| row >= first && row < first + height))
+    }
+
+    fn draw_all_table(&mut self, frame: &mut Frame, area: Rect) {
+        let header = Row::new(vec!["Series", "X", "Y"])
+            .style(Style::default()
+                .fg(self.theme.accent)
+                .add_modifier(Modifier::BOLD))
+            .bottom_margin(1);
+
+        let rows: Vec<Row> = self.table_rows()
+            .into_iter()
+            .map(|(s, i)| {
+                let serie = &self.data_series[s];
+                let (x, y) = serie.data[i];
+                let precision = serie.precision(self.config.precision);
+                let x = match serie.pinned(i) {
+                    true => format!("★ {}", serie.format_x(x, precision)),
+                    false => serie.format_x(x, precision),
+                };
+                Row::new(vec![
+                    Cell::from(serie.name.clone()),
+                    Cell::from(x),
+                    Cell::from(format_value(y, precision)),
+                ])
+                    .style(Style::default().fg(self.serie_color(s)))
+            })
+            .collect();
+        let n_rows = rows.len();
+
+        let widths = [
+            Constraint::Percentage(40),
+            Constraint::Percentage(30),
+            Constraint::Percentage(30),
+        ];
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(Block::bordered()
+                .title("  All series ⇅ ")
+                .title_bottom(Line::from(self.table_order.label()).centered())
+                .title_alignment(Alignment::Center)
+                .padding(Padding::uniform(2)))
+            .column_spacing(1)
+            .row_highlight_style(
+                Style::default()
+                .bg(self.theme.selected_bg)
+                .fg(self.theme.selected_fg)
+                .add_modifier(Modifier::BOLD)
+            );
+
+        let mut state = self.ease_table(area.height.saturating_sub(8) as usize, n_rows);
+        frame.render_stateful_widget(table, area, &mut state);
+    }
+
+    // (series, point) of every point, ordered by x
+    fn all_rows(&self) -> Vec<(usize, usize)> {
+        let mut rows: Vec<(usize, usize)> = self.data_series.iter().enumerate()
+            .flat_map(|(s, serie)| (0..serie.data.len()).map(move |i| (s, i)))
+            .collect();
+        rows.sort_by(|a, b| {
+            let xa = self.data_series[a.0].data[a.1].0;
+            let xb = self.data_series[b.0].data[b.1].0;
+            xa.total_cmp(&xb)
+        });
+        rows
+    }
+
+    fn table_len(&self) -> usize {
+        match self.table_all {
+            true => self.data_series.iter().map(|s| s.data.len()).sum(),
+            false => self.data_series[self.selected_serie].data.len(),
+        }
+    }
+
+    // (series, point) of every table row in display order
+    fn table_rows(&self) -> Vec<(usize, usize)> {
+        let mut rows = match self.table_all {
+            true => self.all_rows(),
+            false => (0..self.data_series[self.selected_serie].data.len()).map(|i| (self.selected_serie, i)).collect(),
+        };
+        if let SortOrder::NewestFirst = self.table_order {
+            rows.reverse();
+        }
+        rows
+    }
+
+    // (series, point) shown in a table row
+    fn table_point(&self, row: usize) -> Option<(usize, usize)> {
+        self.table_rows().get(row).copied()
+    }
+
+
+    fn draw_graph_view(&mut self, frame: &mut Frame) {
+        // KPI strip on top when any are set, in every layout below
+        let area = match self.config.kpis.is_empty() {
+            true => frame.area(),
+            false => {
+                let [kpis, rest] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(frame.area());
+                self.draw_kpis(frame, kpis);
+                rest
+            }
+        };
+        let chunks = Layout::vertical([
+            Constraint::Length(3), // Input
+            Constraint::Min(10), // Graph
         ]).split(area);
 
-        // X
-        let x_style = match (&self.input_mode, &self.input_field) {
-            (InputMode::Insert, InputField::X) => Style::default().fg(Color::Yellow),
-            _ => Style::default(),
+        // Input
+        self.draw_input_bar(frame, chunks[0]);
+
+        // Graph, with the stats panel beside it when there is room
+        match chunks[1].width >= 90 {
+            true => {
+                let [graph, stats] = Layout::horizontal([Constraint::Min(40), Constraint::Length(28)]).areas(chunks[1]);
+                self.draw_graph(frame, graph);
+                self.draw_stats(frame, stats);
+            }
+            false => self.draw_graph(frame, chunks[1]),
+        }
+    }
+
+    // Name, latest value and trend of each KPI series, side by side
+    fn draw_kpis(&self, frame: &mut Frame, area: Rect) {
+        let mut spans = Vec::new();
+        for name in &self.config.kpis {
+            if !spans.is_empty() {
+                spans.push("  │  ".fg(self.theme.muted));
+            }
+            let Some(s) = self.data_series.iter().position(|serie| serie.name == *name) else {
+                spans.push(format!("{} ?", name).dim());
+                continue;
+            };
+            let serie = &self.data_series[s];
+            spans.push("● ".fg(self.serie_color(s)));
+            spans.push(format!("{} ", serie.name).into());
+            spans.push(match serie.data.last() {
+                Some(&(_, y)) => format_value(y, self.config.precision).bold(),
+                None => "-".dim(),
+            });
+            if !serie.unit.is_empty() {
+                spans.push(format!(" {}", serie.unit).dim());
+            }
+            let (arrow, color) = serie.trend_arrow(&self.theme);
+            spans.push(arrow.fg(color));
+        }
+        frame.render_widget(Paragraph::new(Line::from(spans)).centered(), area);
+    }
+
+    fn draw_stats(&self, frame: &mut Frame, area: Rect) {
+        let serie = &self.data_series[self.selected_serie];
+        let precision = serie.precision(self.config.precision);
+        let stat = |name: &str, value: String| Line::from(vec![format!("{:<11}", name).bold(), value.into()]);
+
+        let mut lines = vec![stat("Points", serie.data.len().to_string())];
+        if let Some(&(_, latest)) = serie.data.last() {
+            let summary = serie.summary();
+            lines.push(stat("Min", format_value(summary.y_min, precision)));
+            lines.push(stat("Max", format_value(summary.y_max, precision)));
+            lines.push(stat("Mean", format_value(summary.y_sum / serie.data.len() as f64, precision)));
+            lines.push(stat("Latest", format_value(latest, precision)));
+
+            // Dates count in seconds, plain x as days
+            let day = if serie.data[0].0 >= DATE_MIN { 86400.0 } else { 1.0 };
+            lines.push(Line::from(""));
+            lines.push(Line::from("Recent".bold().underlined()));
+            for rolling in &self.config.rolling {
+                let (name, value) = match rolling.kind {
+                    RollingKind::Mean => ("mean", analysis::window_mean(&serie.data, rolling.days * day)),
+                    RollingKind::Change => ("change", analysis::window_change(&serie.data, rolling.days * day)),
+                };
+                let value = match (rolling.kind, value) {
+                    (RollingKind::Change, Some(v)) if v >= 0.0 => format!("+{}", format_value(v, precision)),
+                    (_, Some(v)) => format_value(v, precision),
+                    (_, None) => "-".to_string(),
+                };
+                lines.push(stat(&format!("{}d {}", rolling.days, name), value));
+            }
+        }
+
+        if let (Some(goal), Some(&(x, y))) = (&serie.rate_goal, serie.data.last())
+            && let Some(target) = goal.target(x) {
+            let on_track = match goal.rate < 0.0 {
+                true => y <= target,
+                false => y >= target,
+            };
+            lines.push(Line::from(""));
+            lines.push(Line::from("Rate goal".bold().underlined()));
+            lines.push(stat("Rate", format!("{}/{}", goal.rate, goal.per)));
+            lines.push(stat("Target", format_value(target, precision)));
+            lines.push(match on_track {
+                true => Line::from("On track".fg(self.theme.on_track).bold()),
+                false => Line::from(format!("Off track by {}", format_value((y - target).abs(), precision)).fg(self.theme.off_track).bold()),
+            });
+        }
+
+        if let (Some(goal), Some(&(_, y))) = (serie.goal, serie.data.last()) {
+            lines.push(Line::from(""));
+            lines.push(Line::from("Goal".bold().underlined()));
+            lines.push(stat("Goal", format_value(goal, precision)));
+            let reached = serie.goal_reached();
+            lines.push(stat("To go", format_value(if reached { 0.0 } else { goal - y }, precision)));
+            match serie.goal_eta() {
+                _ if reached => lines.push(Line::from("Reached".fg(self.theme.goal).bold())),
+                Some(eta) => {
+                    lines.push(stat("ETA", serie.describe_x(eta, precision)));
+                    let days = (eta - chrono::Utc::now().timestamp() as f64) / 86400.0;
+                    if eta >= DATE_MIN && days > 0.0 {
+                        lines.push(stat("Days left", format!("{:.0}", days.ceil())));
+                    }
+                }
+                None => lines.push(Line::from("Not getting closer".fg(self.theme.muted))),
+            }
+        }
+
+        let pinned: Vec<usize> = (0..serie.data.len()).filter(|&i| serie.pinned(i)).collect();
+        if !pinned.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from("Pinned".bold().underlined()));
+            for i in pinned {
+                let (x, y) = serie.data[i];
+                lines.push(Line::from(vec![
+                    "★ ".fg(self.theme.accent),
+                    format!("{:<8} ", serie.describe_x(x, precision)).into(),
+                    format_value(y, precision).into(),
+                ]));
+            }
+        }
+
+        let stats = Paragraph::new(Text::from(lines))
+            .block(Block::bordered()
+                .title(" Stats ")
+                .title_alignment(Alignment::Center)
+                .padding(Padding::horizontal(1)));
+        frame.render_widget(stats, area);
+    }
+
+    fn draw_input_bar(&mut self, frame: &mut Frame, area: Rect) {
+        // One box per field: X, every value column and the error
+        let fields = self.input_fields();
+        let serie = &self.data_series[self.selected_serie];
+        let boxes: Vec<(String, String)> = fields.iter()
+            .map(|field| match *field {
+                InputField::X => (self.input_x.clone(), " X ".to_string()),
+                InputField::Y => (self.input_y.clone(), format!(" {} ", serie.y_name())),
+                InputField::Value(j) => (
+                    self.input_values.get(j).cloned().unwrap_or_default(),
+                    format!(" {} ", serie.extra_columns()[j]),
+                ),
+                InputField::Error => (self.input_error.clone(), " ± ".to_string()),
+            })
+            .collect();
+        // Boxes widen with what is typed, for dates and long prefilled values
+        let mut constraints: Vec<Constraint> = boxes.iter()
+            .map(|(content, _)| Constraint::Length((content.chars().count() as u16 + 4).max(8)))
+            .collect();
+        constraints.push(Constraint::Min(20)); // Status
+        let input_chunks = Layout::horizontal(constraints).split(area);
+
+        for (i, (content, title)) in boxes.into_iter().enumerate() {
+            let style = match (&self.input_mode, self.input_field == fields[i]) {
+                (InputMode::Insert, true) => Style::default().fg(self.theme.accent),
+                _ => Style::default(),
+            };
+            self.draw_input_box(frame, input_chunks[i], content, title, style);
+        }
+
+        // Status, with the progress towards the goal on the right
+        let serie = &self.data_series[self.selected_serie];
+        let progress = match (serie.goal, serie.data.last()) {
+            (Some(goal), Some(&(_, y))) if goal != 0.0 => format!(
+                " {} / {} ({:.0}%) ",
+                format_value(y, self.config.precision),
+                format_value(goal, self.config.precision),
+                y / goal * 100.0,
+            ),
+            _ => String::new(),
+        };
+        let status = Paragraph::new(self.status_msg.clone())
+            .block(Block::bordered()
+                .title(match self.read_only {
+                    true => " Status · read-only ",
+                    false => " Status ",
+                })
+                .title(Line::from(progress.fg(self.theme.goal)).right_aligned())
+                .padding(Padding::left(1)));
+        frame.render_widget(status, input_chunks[fields.len()]);
+    }
+
+    fn draw_input_box(&mut self, frame: &mut Frame, area: Rect, content: String, title: String, style: Style) {
+        let input_box = Paragraph::new(content)
+            .block(Block::bordered().title(title).padding(Padding::left(1)))
+            .style(style);
+            
+        frame.render_widget(input_box, area);
+    }
+
+    // Whether a line of OVERLAY_LINES is drawn for the selected series, the
+    // goal only has to be left shown
+    fn line_shown(&self, line: &str) -> bool {
+        let serie = &self.data_series[self.selected_serie];
+        let on = match line {
+            "average" => serie.average.is_some(),
+            "trend" => serie.trend_line,
+            _ => true,
+        };
+        on && !self.hidden.iter().any(|h| h == line)
+    }
+
+    // Lines of the selected series the legend lists, with their colors
+    fn legend_entries(&self) -> Vec<(String, Color)> {
+        let serie = &self.data_series[self.selected_serie];
+        let mut entries = vec![(serie.y_name().to_string(), self.serie_color(self.selected_serie))];
+        if serie.average.is_some() {
+            entries.push(("average".to_string(), self.theme.average));
+        }
+        if serie.trend_line {
+            entries.push(("trend".to_string(), self.theme.trend));
+        }
+        if serie.reference_lines {
+            entries.extend(REFERENCE_LINES.iter().zip(self.theme.references()).map(|(&name, color)| (name.to_string(), color)));
+        }
+        for (j, column) in serie.extra_columns().iter().enumerate() {
+            entries.push((column.clone(), self.theme.column_color(j)));
+        }
+        if serie.rate_goal.is_some() {
+            entries.push(("target".to_string(), self.theme.target));
+        }
+        if serie.goal.is_some() {
+            entries.push(("goal".to_string(), self.theme.goal));
+        }
+        for s in self.overlay_series() {
+            entries.push((self.data_series[s].name.clone(), self.serie_color(s)));
+        }
+        entries
+    }
+
+    // Overlaid series that still exist, without the selected one
+    fn overlay_series(&self) -> Vec<usize> {
+        self.overlay.iter()
+            .filter_map(|name| self.data_series.iter().position(|s| s.name == *name))
+            .filter(|&s| s != self.selected_serie)
+            .collect()
+    }
+
+    // Drawn instead of the chart's own legend so entries can be selected and
+    // hidden ones still listed
+    fn draw_legend(&self, frame: &mut Frame, area: Rect) {
+        let entries = self.legend_entries();
+        let editing = matches!(self.input_mode, InputMode::Legend);
+        if entries.len() < 2 && !editing {
+            return;
+        }
+
+        let lines: Vec<Line> = entries.iter().enumerate()
+            .map(|(i, (name, color))| {
+                let line = match self.hidden.contains(name) {
+                    true => Line::from(vec!["○ ".fg(self.theme.muted), name.clone().fg(self.theme.muted)]),
+                    false => Line::from(vec!["● ".fg(*color), name.clone().into()]),
+                };
+                match editing && i == self.legend_idx {
+                    true => line.reversed(),
+                    false => line,
+                }
+            })
+            .collect();
+
+        let width = entries.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0) as u16 + 6;
+        let inner = Block::bordered().inner(area);
+        let [legend] = Layout::horizontal([Constraint::Length(width)]).flex(Flex::End).areas(inner);
+        let [legend] = Layout::vertical([Constraint::Length(lines.len() as u16 + 2)]).areas(legend);
+        frame.render_widget(Clear, legend);
+        frame.render_widget(Paragraph::new(lines).block(Block::bordered().padding(Padding::horizontal(1))), legend);
+    }
+
+    fn draw_graph(&mut self, frame: &mut Frame, area: Rect) {
+        let serie = &self.data_series[self.selected_serie];
+
+        // Rate goal from its start to the latest point, or at least one period on
+        let target_line: Vec<(f64, f64)> = serie.rate_goal.iter()
+            .filter_map(|goal| {
+                let period = parse_period(&goal.per, goal.start.0 >= DATE_MIN)?;
+                let end = serie.data.last().map_or(goal.start.0, |&(x, _)| x).max(goal.start.0 + period);
+                Some([goal.start, (end, goal.target(end)?)])
+            })
+            .flatten()
+            .collect();
+
+        // Bounds take in every overlaid series not hidden in the legend
+        let overlay: Vec<usize> = self.overlay_series().into_iter()
+            .filter(|&s| !self.data_series[s].data.is_empty() && !self.hidden.contains(&self.data_series[s].name))
+            .collect();
+        let ((x_min, x_max), y_range) = overlay.iter().map(|&s| &self.data_series[s])
+            .fold((serie.get_x_range(), serie.get_y_range(serie.y_axis)), |((x_min, x_max), (y_min, y_max)), other| {
+                let ((x0, x1), (y0, y1)) = (other.get_x_range(), other.get_y_range(serie.y_axis));
+                ((x_min.min(x0), x_max.max(x1)), (y_min.min(y0), y_max.max(y1)))
+            });
+        let x_max = target_line.last().map_or(f64::NEG_INFINITY, |&(x, _)| x).max(x_max);
+        // Everything below is drawn in the y of the chart, log10 on a log scale
+        let log = serie.y_axis == YAxis::Log;
+        let target_line = plot_points(&target_line, log);
+        let x_range = self.x_window.unwrap_or((x_min, x_max));
+        let y_range = self.y_window.unwrap_or(y_range);
+        let [(x_axis, x_bounds), (y_axis, y_bounds)] = serie.get_axes(x_range, y_range, area);
+        let [x_bounds, y_bounds] = self.ease_graph([x_bounds, y_bounds]);
+        let (x_axis, y_axis) = (x_axis.bounds(x_bounds), y_axis.bounds(y_bounds));
+
+        let serie = &self.data_series[self.selected_serie];
+        let shown = |name: &str| !self.hidden.iter().any(|h| h == name);
+        let points = plot_points(serie.visible(x_bounds), log);
+        let dataset = Dataset::default()
+            .marker(self.serie_marker(self.selected_serie))
+            .graph_type(serie.graph_type())
+            .style(Style::default().fg(self.serie_color(self.selected_serie)))
+            .data(&points);
+
+        // Extra value columns
+        let columns_data: Vec<Vec<(f64, f64)>> = (0..serie.extra_columns().len())
+            .map(|j| serie.data.iter().enumerate()
+                .filter_map(|(i, &(x, _))| serie.values(i).get(j).map(|&v| (x, v)))
+                .collect::<Vec<_>>())
+            .map(|data| plot_points(&data, log).into_owned())
+            .collect();
+
+        // Error bars as one vertical segment per point
+        let bars: Vec<[(f64, f64); 2]> = serie.data.iter().enumerate()
+            .filter_map(|(i, &(x, y))| serie.error(i).map(|e| [(x, y - e), (x, y + e)]))
+            .filter_map(|bar| match plot_points(&bar, log)[..] {
+                [bottom, top] => Some([bottom, top]),
+                _ => None,
+            })
+            .collect();
+
+        // Pinned points, labelled once the chart is drawn
+        let pinned: Vec<(f64, f64)> = serie.data.iter().enumerate()
+            .filter(|&(i, _)| serie.pinned(i))
+            .map(|(_, &p)| p)
+            .collect();
+        let pinned_points = plot_points(&pinned, log);
+
+        let mut datasets: Vec<Dataset> = bars.iter()
+            .map(|bar| Dataset::default()
+                .marker(self.fallback(symbols::Marker::Braille))
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(self.theme.muted))
+                .data(bar))
+            .collect();
+        if shown(serie.y_name()) {
+            datasets.push(dataset);
+        }
+        // Moving average from the start so the first visible points average a full window
+        let end = serie.data.partition_point(|p| p.0 <= x_bounds[1]);
+        let dated = serie.data.first().is_some_and(|&(x, _)| x >= DATE_MIN);
+        let average: Vec<(f64, f64)> = match serie.average.as_deref().and_then(|a| parse_period(a, dated)) {
+            Some(span) if shown("average") => analysis::moving_average(&serie.data[..(end + 1).min(serie.data.len())], span),
+            _ => Vec::new(),
+        };
+        let average = plot_points(&average, log);
+        if !average.is_empty() {
+            datasets.push(Dataset::default()
+                .marker(self.serie_marker(self.selected_serie))
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(self.theme.average))
+                .data(&average));
+        }
+        // Sampled so it still follows the fit on a log scale
+        let trend: Vec<(f64, f64)> = match serie.trend_fit(self.x_window) {
+            Some((slope, intercept)) if self.line_shown("trend") => (0..=32)
+                .map(|i| x_bounds[0] + (x_bounds[1] - x_bounds[0]) * i as f64 / 32.0)
+                .map(|x| (x, slope * x + intercept))
+                .collect(),
+            _ => Vec::new(),
+        };
+        let trend = plot_points(&trend, log);
+        if !trend.is_empty() {
+            datasets.push(Dataset::default()
+                .marker(self.fallback(symbols::Marker::Braille))
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(self.theme.trend))
+                .data(&trend));
+        }
+        for (j, data) in columns_data.iter().enumerate() {
+            if !shown(&serie.extra_columns()[j]) {
+                continue;
+            }
+            datasets.push(Dataset::default()
+                .marker(self.serie_marker(self.selected_serie))
+                .graph_type(serie.graph_type())
+                .style(Style::default().fg(self.theme.column_color(j)))
+                .data(data));
+        }
+        // Goal across the whole chart
+        let goal_line: Vec<(f64, f64)> = serie.goal.iter()
+            .flat_map(|&goal| [(x_bounds[0], goal), (x_bounds[1], goal)])
+            .collect();
+        let goal_line = plot_points(&goal_line, log);
+        if !goal_line.is_empty() && shown("goal") {
+            datasets.push(Dataset::default()
+                .marker(self.fallback(symbols::Marker::Braille))
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(self.theme.goal))
+                .data(&goal_line));
+        }
+        // Mean, min and max across the chart, labelled once it is drawn
+        let references: Vec<_> = REFERENCE_LINES.iter().zip(self.theme.references()).zip(serie.reference_values())
+            .filter(|&((&name, _), _)| serie.reference_lines && shown(name))
+            .map(|((&name, color), y)| (name, color, y, plot_points(&[(x_bounds[0], y), (x_bounds[1], y)], log).into_owned()))
+            .filter(|(_, _, _, line)| !line.is_empty())
+            .collect();
+        for (_, color, _, line) in &references {
+            datasets.push(Dataset::default()
+                .marker(self.fallback(symbols::Marker::Braille))
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(*color))
+                .data(line));
+        }
+        if !target_line.is_empty() && shown("target") {
+            datasets.push(Dataset::default()
+                .marker(self.fallback(symbols::Marker::Braille))
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(self.theme.target))
+                .data(&target_line));
+        }
+        let overlay_points: Vec<_> = overlay.iter().map(|&s| plot_points(self.data_series[s].visible(x_bounds), log)).collect();
+        for (&s, points) in overlay.iter().zip(&overlay_points) {
+            datasets.push(Dataset::default()
+                .marker(self.serie_marker(s))
+                .graph_type(self.data_series[s].graph_type())
+                .style(Style::default().fg(self.serie_color(s)))
+                .data(points));
+        }
+        if !pinned_points.is_empty() {
+            datasets.push(Dataset::default()
+                .marker(symbols::Marker::Dot)
+                .graph_type(GraphType::Scatter)
+                .style(Style::default().fg(self.theme.accent))
+                .data(&pinned_points));
+        }
+        let cursor: Vec<(f64, f64)> = match self.input_mode {
+            InputMode::Cursor => serie.data.get(self.cursor).copied().into_iter().collect(),
+            _ => Vec::new(),
+        };
+        let cursor = plot_points(&cursor, log);
+        // Crosshair through the point under the cursor, drawn beneath the lines
+        let crosshair: Vec<[(f64, f64); 2]> = cursor.iter()
+            .flat_map(|&(x, y)| [[(x, y_bounds[0]), (x, y_bounds[1])], [(x_bounds[0], y), (x_bounds[1], y)]])
+            .collect();
+        for line in &crosshair {
+            datasets.insert(0, Dataset::default()
+                .marker(self.fallback(symbols::Marker::Braille))
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(self.theme.muted))
+                .data(line));
+        }
+        if !cursor.is_empty() {
+            datasets.push(Dataset::default()
+                .marker(symbols::Marker::Block)
+                .graph_type(GraphType::Scatter)
+                .style(Style::default().fg(self.theme.alert))
+                .data(&cursor));
+        }
+
+        let name = overlay.iter().fold(serie.name.clone(), |title, &s| format!("{} + {}", title, self.data_series[s].name));
+        let title = match serie.goal {
+            Some(goal) => format!(" {} · goal {} {} ", name, goal, serie.unit),
+            None => format!(" {} ", name),
+        };
+        // A log axis is labelled with the values as stored
+        let unit = match log {
+            true => serie.unit.clone(),
+            false => serie.y_unit().0,
+        };
+        let y_title = match unit.is_empty() {
+            true => "Y".to_string(),
+            false => format!("Y ({})", unit),
+        };
+
+        let chart = Chart::new(datasets)
+            .block(Block::bordered()
+                .title(title)
+                .title_alignment(Alignment::Center))
+            .x_axis(x_axis.title("X"))
+            .y_axis(y_axis.title(y_title));
+
+        frame.render_widget(chart, area);
+        self.graph_plot = plot_area(frame, area).map(|plot| (plot, x_bounds, y_bounds));
+        self.draw_pin_labels(frame, area, &pinned, x_bounds, y_bounds);
+        let labels: Vec<(f64, String, Color)> = references.iter()
+            .map(|(name, color, y, line)| (line[0].1, format!("{} {}", name, format_value(*y, serie.precision(self.config.precision))), *color))
+            .collect();
+        self.draw_line_labels(frame, area, &labels, y_bounds);
+        self.draw_legend(frame, area);
+    }
+
+    // Labels flat lines at the left of the plot, `labels` holds the y they are
+    // drawn at. Runs after the chart is rendered, like draw_pin_labels
+    fn draw_line_labels(&self, frame: &mut Frame, area: Rect, labels: &[(f64, String, Color)], [y0, y1]: [f64; 2]) {
+        let Some(plot) = plot_area(frame, area) else { return };
+
+        for (y, label, color) in labels {
+            if *y < y0 || *y > y1 {
+                continue;
+            }
+            let row = plot.y + ((y1 - y) / (y1 - y0) * (plot.height - 1) as f64) as u16;
+            let width = (label.chars().count() as u16).min(plot.width.saturating_sub(1));
+            frame.render_widget(Paragraph::new(label.clone()).fg(*color), Rect::new(plot.x + 1, row, width, 1));
+        }
+    }
+
+    // Bounds to draw the graph with. Switching series or zooming eases from
+    // the bounds last drawn to the new ones, the labels show the new ones
+    fn ease_graph(&mut self, target: [[f64; 2]; 2]) -> [[f64; 2]; 2] {
+        let view = (self.selected_serie, [self.x_window, self.y_window]);
+        if self.graph_view.is_some_and(|last| last != view) && self.config.animations {
+            self.graph_anim = Some((self.graph_shown, Instant::now()));
+        }
+
+        let bounds = match self.graph_anim.and_then(|(from, start)| Some((from, ease(start)?))) {
+            Some((from, t)) => [0, 1].map(|a| [0, 1].map(|b| from[a][b] + (target[a][b] - from[a][b]) * t)),
+            None => {
+                self.graph_anim = None;
+                target
+            }
+        };
+        self.graph_view = Some(view);
+        self.graph_shown = bounds;
+        bounds
+    }
+
+    // Labels pinned points where the chart drew them. The plot area is found
+    // from the corner of the axes, so this has to run after the chart is rendered
+    fn draw_pin_labels(&self, frame: &mut Frame, area: Rect, pinned: &[(f64, f64)], [x0, x1]: [f64; 2], [y0, y1]: [f64; 2]) {
+        let Some(plot) = plot_area(frame, area) else { return };
+        let log = self.data_series[self.selected_serie].y_axis == YAxis::Log;
+
+        for &(x, value) in pinned {
+            let y = if log { value.log10() } else { value };
+            if (log && value <= 0.0) || x < x0 || x > x1 || y < y0 || y > y1 {
+                continue;
+            }
+            // Same truncating mapping the chart's canvas uses
+            let col = plot.x + ((x - x0) / (x1 - x0) * (plot.width - 1) as f64) as u16;
+            let row = plot.y + ((y1 - y) / (y1 - y0) * (plot.height - 1) as f64) as u16;
+            let label = format!("★{}", format_value(value, self.config.precision));
+            let width = (label.chars().count() as u16).min(plot.right().saturating_sub(col + 1));
+            frame.render_widget(Paragraph::new(label).fg(self.theme.accent), Rect::new(col + 1, row, width, 1));
+        }
+    }
+
+    fn draw_spectrum_view(&mut self, frame: &mut Frame) {
+        let chunks = Layout::vertical([
+            Constraint::Min(10), // Spectrum
+            Constraint::Length(1), // Footer
+        ]).split(frame.area());
+
+        let serie = &self.data_series[self.selected_serie];
+        let spectrum = match analysis::power_spectrum(&serie.data) {
+            Ok(spectrum) => spectrum,
+            Err(e) => {
+                let content = Paragraph::new(format!("{} (h: help)", e)).centered();
+                frame.render_widget(content, center(frame.area(), Constraint::Percentage(100), Constraint::Length(1)));
+                return;
+            }
+        };
+
+        let f_max = spectrum.last().map_or(1.0, |&(f, _)| f);
+        let p_max = spectrum.iter().fold(0.0_f64, |acc, &(_, p)| acc.max(p)).max(f64::EPSILON);
+
+        let dataset = Dataset::default()
+            .name("")
+            .marker(self.fallback(symbols::Marker::Braille))
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(self.theme.spectrum))
+            .data(&spectrum);
+
+        let chart = Chart::new(vec![dataset])
+            .block(Block::bordered()
+                .title(format!(" {} · Power spectrum ", serie.name))
+                .title_alignment(Alignment::Center))
+            .x_axis(nice_axis(0.0, f_max, 5).0.title("Frequency"))
+            .y_axis(nice_axis(0.0, p_max, 5).0.title("Power"));
+
+        frame.render_widget(chart, chunks[0]);
+
+        // Dominant frequency
+        let (f_peak, _) = spectrum.iter()
+            .fold((0.0, f64::NEG_INFINITY), |acc, &(f, p)| if p > acc.1 { (f, p) } else { acc });
+        let footer = Paragraph::new(format!("Peak: f = {:.4}, period = {:.2}    h: help", f_peak, 1.0 / f_peak)).centered();
+        frame.render_widget(footer, chunks[1]);
+    }
+
+    // The period holding the latest point over the one before it, x counted
+    // from each period's start (in days for dates)
+    fn draw_compare_view(&mut self, frame: &mut Frame) {
+        let chunks = Layout::vertical([
+            Constraint::Min(10), // Chart
+            Constraint::Length(8), // Summary
+        ]).split(frame.area());
+
+        let serie = &self.data_series[self.selected_serie];
+        let bounds = serie.data.last().and_then(|&(x, _)| period_bounds(x, self.compare_period));
+        let Some([previous_start, start, end]) = bounds else {
+            let content = Paragraph::new("No points to compare (h: help)").centered();
+            frame.render_widget(content, center(frame.area(), Constraint::Percentage(100), Constraint::Length(1)));
+            return;
+        };
+
+        let scale = if start >= DATE_MIN { 86400.0 } else { 1.0 };
+        let rebase = |from: f64, to: f64| -> Vec<(f64, f64)> {
+            serie.data.iter()
+                .filter(|&&(x, _)| x >= from && x < to)
+                .map(|&(x, y)| ((x - from) / scale, y))
+                .collect()
+        };
+        let previous = rebase(previous_start, start);
+        let current = rebase(start, end);
+
+        let (period, x_title) = match (self.compare_period, scale > 1.0) {
+            (Period::Week, true) => ("week", "Day of week"),
+            (Period::Month, true) => ("month", "Day of month"),
+            (Period::Week, false) => ("week", "X from week start"),
+            (Period::Month, false) => ("month", "X from month start"),
+        };
+
+        let ys = previous.iter().chain(&current).map(|&(_, y)| y);
+        let y_min = ys.clone().fold(f64::INFINITY, f64::min);
+        let y_max = ys.fold(f64::NEG_INFINITY, f64::max);
+        let pad = if y_max > y_min { (y_max - y_min) * 0.1 } else { 1.0 };
+        let x_len = (start - previous_start).max(end - start) / scale;
+        let precision = serie.precision(self.config.precision);
+        let previous_ys: Vec<f64> = previous.iter().map(|&(_, y)| y).collect();
+        let current_ys: Vec<f64> = current.iter().map(|&(_, y)| y).collect();
+
+        let datasets = vec![
+            Dataset::default()
+                .name(format!("last {}", period))
+                .marker(self.marker())
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(self.theme.muted))
+                .data(&previous),
+            Dataset::default()
+                .name(format!("this {}", period))
+                .marker(self.marker())
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(self.serie_color(self.selected_serie)))
+                .data(&current),
+        ];
+
+        match self.compare_histogram {
+            false => {
+                let chart = Chart::new(datasets)
+                    .block(Block::bordered()
+                        .title(format!(" {} · this {} vs last ", serie.name, period))
+                        .title_alignment(Alignment::Center))
+                    .x_axis(nice_axis(0.0, x_len, 5).0.title(x_title))
+                    .y_axis(nice_axis(y_min - pad, y_max + pad, 5).0.title("Y"));
+                frame.render_widget(chart, chunks[0]);
+            }
+            true => {
+                // Bins on round y values, bars give the share of each span's points
+                // so a span with fewer points still compares
+                let edges = analysis::nice_ticks(y_min, y_max, 8);
+                let counts = [analysis::histogram(&previous_ys, &edges), analysis::histogram(&current_ys, &edges)];
+                let share = |count: usize, total: usize| (count * 100).checked_div(total).unwrap_or(0) as u64;
+                let styles = [Style::default().fg(self.theme.muted), Style::default().fg(self.serie_color(self.selected_serie))];
+                let totals = [previous_ys.len(), current_ys.len()];
+
+                let bins = edges.len() - 1;
+                let bar_width = ((chunks[0].width.saturating_sub(2) / bins as u16).saturating_sub(1) / 2).max(1);
+                let mut chart = BarChart::default()
+                    .block(Block::bordered()
+                        .title(format!(" {} · y this {} (color) vs last (gray), % of points ", serie.name, period))
+                        .title_alignment(Alignment::Center))
+                    .bar_width(bar_width)
+                    .bar_gap(0)
+                    .group_gap(1);
+                for i in 0..bins {
+                    let bars: Vec<Bar> = (0..2)
+                        .map(|p| {
+                            let value = share(counts[p][i], totals[p]);
+                            Bar::default()
+                                .value(value)
+                                .text_value(if bar_width >= 3 { format!("{}%", value) } else { String::new() })
+                                .style(styles[p])
+                        })
+                        .collect();
+                    chart = chart.data(BarGroup::default()
+                        .label(Line::from(format_value(edges[i], precision)).centered())
+                        .bars(&bars));
+                }
+                frame.render_widget(chart, chunks[0]);
+            }
+        }
+
+        // Summary
+        let mean = |data: &[(f64, f64)]| (!data.is_empty()).then(|| data.iter().map(|&(_, y)| y).sum::<f64>() / data.len() as f64);
+        let change = |data: &[(f64, f64)]| Some(data.last()?.1 - data.first()?.1);
+        let cell = |v: Option<f64>| v.map_or("-".to_string(), |v| format_value(v, precision));
+        let delta = |a: Option<f64>, b: Option<f64>| match (a, b) {
+            (Some(a), Some(b)) if b >= a => format!("+{}", format_value(b - a, precision)),
+            (Some(a), Some(b)) => format_value(b - a, precision),
+            _ => "-".to_string(),
+        };
+
+        let counts = (Some(previous.len() as f64), Some(current.len() as f64));
+        let means = (mean(&previous), mean(&current));
+        let changes = (change(&previous), change(&current));
+        let spreads = (analysis::std_dev(&previous_ys), analysis::std_dev(&current_ys));
+        let rows = vec![
+            Row::new(vec!["Points".to_string(), cell(counts.0), cell(counts.1), delta(counts.0, counts.1)]),
+            Row::new(vec!["Mean".to_string(), cell(means.0), cell(means.1), delta(means.0, means.1)]),
+            Row::new(vec!["Change".to_string(), cell(changes.0), cell(changes.1), delta(changes.0, changes.1)]),
+            Row::new(vec!["Std dev".to_string(), cell(spreads.0), cell(spreads.1), delta(spreads.0, spreads.1)]),
+        ];
+        let header = Row::new(vec!["".to_string(), format!("Last {}", period), format!("This {}", period), "Δ".to_string()])
+            .style(Style::default()
+                .fg(self.theme.accent)
+                .add_modifier(Modifier::BOLD));
+
+        let area = center(chunks[1], Constraint::Length(56), Constraint::Percentage(100));
+        let summary = Table::new(rows, [Constraint::Ratio(1, 4); 4])
+            .header(header)
+            .block(Block::default()
+                .title_bottom(Line::from(" p: week / month  d: lines / histogram  h: help ").centered())
+                .padding(Padding::vertical(1)));
+        frame.render_widget(summary, area);
+    }
+
+    fn draw_batch_view(&mut self, frame: &mut Frame) {
+        let serie = &self.data_series[self.selected_serie];
+        let fields = self.input_fields();
+
+        let mut columns = vec!["#".to_string()];
+        columns.extend(fields.iter().map(|field| match *field {
+            InputField::X => "X".to_string(),
+            InputField::Y => serie.y_name().to_string(),
+            InputField::Value(j) => serie.extra_columns()[j].clone(),
+            InputField::Error => "±".to_string(),
+        }));
+        let header = Row::new(columns)
+            .style(Style::default()
+                .fg(self.theme.accent)
+                .add_modifier(Modifier::BOLD))
+            .bottom_margin(1);
+
+        let (row_idx, col_idx) = self.batch_cell;
+        let rows: Vec<Row> = self.batch.iter().enumerate()
+            .map(|(i, row)| {
+                let mut cells = vec![Cell::from((i + 1).to_string().dim())];
+                for (j, text) in row.iter().enumerate() {
+                    cells.push(match (i, j) == (row_idx, col_idx) {
+                        true => Cell::from(format!("{}_", text)).reversed(),
+                        false => Cell::from(text.clone()),
+                    });
+                }
+                match self.batch_invalid.contains(&i) {
+                    true => Row::new(cells).fg(self.theme.alert),
+                    false => Row::new(cells),
+                }
+            })
+            .collect();
+
+        // X has room for a date and time
+        let mut widths = vec![Constraint::Length(3), Constraint::Length(16)];
+        widths.extend(vec![Constraint::Length(12); fields.len() - 1]);
+        let width = 3 + 17 + 13 * (fields.len() - 1) as u16 + 6;
+        let area = center(frame.area(), Constraint::Length(width.max(64)), Constraint::Percentage(80));
+
+        let mut state = TableState::default().with_selected(Some(row_idx));
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(Block::bordered()
+                .title(format!(" Batch entry · {} ", serie.name))
+                .title_alignment(Alignment::Center)
+                .title_bottom(Line::from(" TAB/ENTER: next  d: delete row  w: commit  ESC: discard ").centered())
+                .padding(Padding::uniform(1)))
+            .column_spacing(1);
+        frame.render_stateful_widget(table, area, &mut state);
+
+        let [_, status] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        frame.render_widget(Paragraph::new(self.status_msg.clone()).centered(), status);
+    }
+
+    // The configured frame, stretched while redraws take more than half of
+    // it so big series leave time to handle the input between frames
+    fn frame_time(&self) -> Duration {
+        let frame = Duration::from_secs_f64(1.0 / self.config.refresh_rate.max(1) as f64);
+        frame.max((self.draw_time * 2).min(MAX_FRAME_TIME))
+    }
+
+    // Blocks for the next event, then keeps consuming whatever arrives until
+    // the frame budget is spent so bursts of input only cost a single redraw
+    fn handle_events(&mut self) -> Result<()> {
+        // Wake up for the next autosave, API requests and changes to data.csv even without input
+        let autosave_due = self.autosave_interval().map(|interval| self.last_save.unwrap_or_else(Instant::now) + interval);
+        loop {
+            if self.handle_api_requests() || self.poll_import() || self.poll_loading() || self.check_data_file() {
+                return Ok(());
+            }
+            let mut timeout = autosave_due.map_or(WATCH_POLL, |due| due.saturating_duration_since(Instant::now()).min(WATCH_POLL));
+            if self.api.is_some() || self.import_job.is_some() || self.loading.is_some() {
+                timeout = timeout.min(API_POLL);
+            }
+            // Redraw every frame while something is moving
+            let animating = self.graph_anim.is_some() || self.table_anim.is_some();
+            if animating {
+                timeout = timeout.min(self.frame_time());
+            }
+            if event::poll(timeout)? {
+                break;
+            }
+            if animating {
+                return Ok(());
+            }
+            if autosave_due.is_some_and(|due| Instant::now() >= due) {
+                return Ok(());
+            }
+        }
+        self.handle_event(event::read()?);
+
+        let deadline = self.last_draw.unwrap_or_else(Instant::now) + self.frame_time();
+        while !self.exit {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if !event::poll(timeout)? {
+                break;
+            }
+            self.handle_event(event::read()?);
+        }
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: Event) {
+        if let Event::Key(key) = event
+            && key.kind == KeyEventKind::Press {
+            if key.code == KeyCode::Char('z') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                self.suspend_pending = true;
+                return;
+            }
+
+            if let Some(report) = &mut self.merge_report {
+                match (key.code, report.pending.take()) {
+                    (KeyCode::Enter, Some(loaded)) => {
+                        self.merge_series(loaded, false);
+                        self.status_msg = "Applied the changes to data.csv".to_string();
+                        self.merge_report = None;
+                    }
+                    (KeyCode::Enter | KeyCode::Esc, _) => self.merge_report = None,
+                    (_, pending) => report.pending = pending,
+                }
+                return;
+            }
+
+            if let Some(recovery) = &mut self.recovery {
+                match key.code {
+                    KeyCode::Left => recovery.choice = recovery.choice.saturating_sub(1),
+                    KeyCode::Right | KeyCode::Tab => recovery.choice = (recovery.choice + 1) % RECOVERY_CHOICES.len(),
+                    KeyCode::Up | KeyCode::Char('k') => recovery.scroll = recovery.scroll.saturating_sub(1),
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        recovery.scroll = (recovery.scroll + 1).min(recovery.rejected.len().saturating_sub(1));
+                    }
+                    KeyCode::Enter => {
+                        let choice = recovery.choice;
+                        self.resolve_recovery(choice);
+                    }
+                    _ => {}
+                }
+                return;
+            }
+
+            if let Some(findings) = &self.doctor {
+                match key.code {
+                    KeyCode::Up | KeyCode::Char('k') => self.doctor_idx = self.doctor_idx.saturating_sub(1),
+                    KeyCode::Down | KeyCode::Char('j') => self.doctor_idx = (self.doctor_idx + 1).min(findings.len() - 1),
+                    KeyCode::Enter => {
+                        let s = findings[self.doctor_idx].serie;
+                        self.doctor = None;
+                        self.select_serie(s);
+                        self.mode = ViewMode::Graph;
+                    }
+                    KeyCode::Esc | KeyCode::Char('q') => self.doctor = None,
+                    _ => {}
+                }
+                return;
+            }
+
+            if self.duplicate.is_some() {
+                match key.code {
+                    KeyCode::Left => self.duplicate_idx = self.duplicate_idx.saturating_sub(1),
+                    KeyCode::Right | KeyCode::Tab => self.duplicate_idx = (self.duplicate_idx + 1) % DUPLICATE_CHOICES.len(),
+                    KeyCode::Enter => self.resolve_duplicate(self.duplicate_idx),
+                    _ => {}
+                }
+                return;
+            }
+
+            if let Some(pending) = self.pending_insert.take() {
+                match key.code {
+                    KeyCode::Enter => self.insert_points(pending.points),
+                    KeyCode::Esc => self.status_msg = "Nothing inserted".to_string(),
+                    _ => self.pending_insert = Some(pending),
+                }
+                return;
+            }
+
+            match (self.tour, key.code) {
+                (Some(_), KeyCode::F(10)) => {
+                    self.tour = None;
+                    return;
+                }
+                (Some(0), KeyCode::Enter) if !self.is_typing() => {
+                    self.tour = Some(1);
+                    return;
+                }
+                (Some(step), KeyCode::Enter) if step == TOUR.len() - 1 && !self.is_typing() => {
+                    self.tour = None;
+                    return;
+                }
+                _ => {}
+            }
+
+            // ALT+1-9 also works where the digits are taken
+            if let KeyCode::Char(c @ '1'..='9') = key.code
+                && key.modifiers.contains(KeyModifiers::ALT) {
+                self.select_favorite(c as usize - '0' as usize);
+                return;
+            }
+
+            if !self.is_typing() {
+                match key.code {
+                    KeyCode::Char('Q') => {
+                        self.toggle_recording();
+                        return;
+                    }
+                    KeyCode::Char('@') => {
+                        self.replay_macro();
+                        return;
+                    }
+                    _ => {}
+                }
+            }
+
+            if let Some(keys) = &mut self.recording {
+                keys.push(key.code);
+            }
+            self.handle_key(key.code);
+            self.advance_tour();
+        } else if let Event::Mouse(mouse) = event
+            && mouse.kind == MouseEventKind::Down(MouseButton::Left) {
+            self.handle_click(mouse.column, mouse.row);
+        }
+    }
+
+    // A click in the graph fills the insert bar with the point under the
+    // mouse, rounded to the click steps
+    fn handle_click(&mut self, column: u16, row: u16) {
+        let Some((plot, [x0, x1], [y0, y1])) = self.graph_plot else { return };
+        if self.mode != ViewMode::Graph
+            || !plot.contains(Position::new(column, row))
+            || matches!(self.input_mode, InputMode::Cursor | InputMode::Legend)
+            || self.command.is_some()
+            || !self.writable() {
+            return;
+        }
+
+        // Inverse of the mapping in draw_pin_labels
+        let x = x0 + (column - plot.x) as f64 / (plot.width - 1) as f64 * (x1 - x0);
+        let y = y1 - (row - plot.y) as f64 / (plot.height - 1) as f64 * (y1 - y0);
+        let y = match self.data_series[self.selected_serie].y_axis {
+            YAxis::Log => 10f64.powf(y),
+            _ => y,
+        };
+        let snap = |v: f64, step: f64| match step > 0.0 {
+            true => round_like((v / step).round() * step, &[step]),
+            false => v,
+        };
+
+        let dated = x0 >= DATE_MIN;
+        let x = snap(x, if dated { self.config.click_step_x * 86400.0 } else { self.config.click_step_x });
+        // Value fields sized for the series, before X and Y are filled in
+        if !matches!(self.input_mode, InputMode::Insert) {
+            self.input_mode = InputMode::Insert;
+            self.clear_inputs();
+        }
+        self.input_x = match dated {
+            true => chrono::DateTime::from_timestamp(x as i64, 0)
+                .map_or_else(|| x.to_string(), |d| d.format("%Y-%m-%d").to_string()),
+            false => format_value(x, self.config.precision),
+        };
+        let precision = self.data_series[self.selected_serie].precision(self.config.precision);
+        self.input_y = format_value(snap(y, self.config.click_step_y), precision);
+        self.input_field = InputField::Y;
+        self.status_msg = "Clicked point, ENTER: insert  ESC: cancel".to_string();
+    }
+
+    // Answers pending API requests, true if there were any so the UI redraws
+    fn handle_api_requests(&mut self) -> bool {
+        // Requests wait for the points, or time out as busy
+        let Some(api) = self.api.as_ref().filter(|_| self.loading.is_none()) else { return false };
+        let requests: Vec<ApiRequest> = api.try_iter().collect();
+        for request in &requests {
+            let (status, body) = self.handle_api(&request.method, &request.path, &request.body);
+            let _ = request.reply.send((status, body.to_string()));
+        }
+        !requests.is_empty()
+    }
+
+    // GET /series, GET /series/<name>/latest and POST /series/<name>/points
+    fn handle_api(&mut self, method: &str, path: &[String], body: &str) -> (u16, serde_json::Value) {
+        self.refresh_aggregates();
+        let path: Vec<&str> = path.iter().map(String::as_str).collect();
+        match (method, path.as_slice()) {
+            ("GET", ["series"]) => {
+                let series: Vec<serde_json::Value> = self.data_series.iter()
+                    .map(|s| serde_json::json!({ "name": s.name, "unit": s.unit, "points": s.data.len() }))
+                    .collect();
+                (200, serde_json::json!(series))
+            }
+            ("GET", ["series", name, "latest"]) => match self.data_series.iter().find(|s| s.name == *name) {
+                Some(serie) => match serie.data.last() {
+                    Some(&(x, y)) => (200, serde_json::json!({ "x": x, "y": y, "unit": serie.unit })),
+                    None => (404, serde_json::json!({ "error": "series has no points" })),
+                },
+                None => (404, serde_json::json!({ "error": "no such series" })),
+            },
+            ("POST", ["series", name, "points"]) => self.api_append(name, body),
+            _ => (404, serde_json::json!({ "error": "unknown endpoint" })),
+        }
+    }
+
+    // Body {"y": 80.2} with optional "x" (defaults to now as a UNIX timestamp)
+    // and "force" to insert in spite of the series' rules
+    fn api_append(&mut self, name: &str, body: &str) -> (u16, serde_json::Value) {
+        if self.read_only {
+            return (403, serde_json::json!({ "error": "read-only" }));
+        }
+        let point: ApiPoint = match serde_json::from_str(body) {
+            Ok(point) => point,
+            Err(e) => return (400, serde_json::json!({ "error": e.to_string() })),
+        };
+        let Some(serie) = self.data_series.iter_mut().find(|s| s.name == name) else {
+            return (404, serde_json::json!({ "error": "no such series" }));
+        };
+        if serie.aggregate.is_some() {
+            return (400, serde_json::json!({ "error": "computed from its group" }));
+        }
+
+        let x = point.x.unwrap_or_else(|| chrono::Utc::now().timestamp() as f64);
+        let points = vec![(x, point.y, PointMeta::default())];
+        let rejections = serie.rejections(&points);
+        if !rejections.is_empty() {
+            return (400, serde_json::json!({ "error": "x is not after the latest point", "violations": rejections }));
+        }
+        let violations = serie.violations(&points);
+        if !violations.is_empty() && !point.force {
+            return (400, serde_json::json!({ "error": "breaks the series' rules", "violations": violations }));
+        }
+
+        serie.insert_point(x, point.y, PointMeta::default());
+        self.status_msg = format!("Logged {} to {} over the API", point.y, serie.name);
+        if let Some(s) = self.data_series.iter().position(|s| s.name == name) {
+            self.point_added(s, x, point.y);
+        }
+        (201, serde_json::json!({ "x": x, "y": point.y }))
+    }
+
+    // Runs the point hooks. The goal counts as reached when the new point
+    // lands on it or on the other side of it than the point before
+    fn point_added(&mut self, s: usize, x: f64, y: f64) {
+        let serie = &self.data_series[s];
+        let name = serie.name.clone();
+        let reached = serie.goal.filter(|&goal| {
+            let i = serie.data.partition_point(|p| p.0 < x);
+            match i.checked_sub(1).map(|j| serie.data[j].1) {
+                Some(previous) => previous != goal && (previous - goal) * (y - goal) <= 0.0,
+                None => y == goal,
+            }
+        });
+
+        let args = vec![name, x.to_string(), y.to_string()];
+        self.run_hook(self.config.hooks.on_point_added.clone(), &args);
+        if let Some(goal) = reached {
+            let mut args = args;
+            args.push(goal.to_string());
+            self.run_hook(self.config.hooks.on_goal_reached.clone(), &args);
+        }
+    }
+
+    // Not in demo mode, the generated series are not the user's data
+    fn run_hook(&mut self, command: String, args: &[String]) {
+        if command.is_empty() || self.demo {
+            return;
+        }
+        if let Err(e) = hooks::run(&command, args) {
+            self.status_msg = format!("Hook failed: {}", e);
+        }
+    }
+
+    fn autosave_interval(&self) -> Option<Duration> {
+        (self.config.autosave > 0).then(|| Duration::from_secs(self.config.autosave as u64 * 60))
+    }
+
+    fn autosave(&mut self) {
+        let Some(interval) = self.autosave_interval() else { return };
+        if self.demo || self.read_only || self.last_save.is_some_and(|t| t.elapsed() < interval) {
+            return;
+        }
+
+        self.last_save = Some(Instant::now());
+        let result = self.save_points()
+            .and_then(|changed| Ok((changed, self.save_meta()?)));
+        self.data_mtime = Self::data_file_mtime();
+        match result {
+            Ok((None, false)) => {}
+            Ok((changed, _)) => {
+                let mut args = vec!["data.csv".to_string()];
+                args.extend(changed.unwrap_or_default());
+                self.run_hook(self.config.hooks.on_save.clone(), &args);
+            }
+            Err(e) => self.status_msg = format!("Autosave failed: {}", e),
+        }
+    }
+
+    // Text is being entered, so letters are input rather than commands
+    fn is_typing(&self) -> bool {
+        matches!(self.input_mode, InputMode::Insert) || self.command.is_some() || self.settings_edit.is_some()
+            || self.browser.is_some() || self.series_search.is_some()
+    }
+
+    fn handle_key(&mut self, key: KeyCode) {
+        if self.template_idx.is_some() {
+            self.handle_template_input(key);
+            return;
+        }
+
+        if self.browser.is_some() {
+            self.handle_browser_input(key);
+            return;
+        }
+
+        if self.command.is_some() {
+            self.handle_command_input(key);
+            return;
+        }
+
+        if key == KeyCode::Char(':') && !self.is_typing() {
+            self.command = Some(String::new());
+            return;
+        }
+
+        // 1-4 pick the time window in the graph, batch entry types digits
+        if let KeyCode::Char(c @ '1'..='9') = key
+            && !self.is_typing()
+            && self.mode != ViewMode::Batch
+            && !(self.mode == ViewMode::Graph && c <= '4') {
+            self.select_favorite(c as usize - '0' as usize);
+            return;
+        }
+
+        match self.mode {
+            ViewMode::Graph => self.handle_graph_input(key),
+            ViewMode::Table => self.handle_table_input(key),
+            ViewMode::Spectrum => self.handle_spectrum_input(key),
+            ViewMode::Menu => self.handle_menu_input(key),
+            ViewMode::Series => self.handle_series_input(key),
+            ViewMode::Trash => self.handle_trash_input(key),
+            ViewMode::Help => self.handle_help_input(key),
+            ViewMode::Settings => self.handle_settings_input(key),
+            ViewMode::Compare => self.handle_compare_input(key),
+            ViewMode::Batch => self.handle_batch_input(key),
+        }
+    }
+
+    fn handle_command_input(&mut self, key: KeyCode) {
+        let Some(command) = &mut self.command else { return };
+
+        match key {
+            KeyCode::Char(c) => command.push(c),
+            KeyCode::Backspace if command.is_empty() => self.command = None,
+            KeyCode::Backspace => {
+                command.pop();
+            }
+            KeyCode::Enter => {
+                let command = self.command.take().unwrap_or_default();
+                self.run_command(&command);
+                self.find_duplicates();
+            }
+            KeyCode::Esc => {
+                self.command = None;
+                // Back to the dialog when renaming a duplicate was cancelled
+                self.find_duplicates();
+            }
+            _ => {}
+        }
+    }
+
+    fn run_command(&mut self, command: &str) {
+        let command = command.trim();
+        let (name, arg) = command.split_once(' ').map_or((command, ""), |(name, arg)| (name, arg.trim()));
+        match (name, arg) {
+            ("", _) => {}
+            ("goto", "") => self.status_msg = "Usage: goto <x, YYYY-MM-DD or YYYY-MM-DD HH:MM>".to_string(),
+            ("goto", arg) => self.goto(arg),
+            ("series", "") => self.status_msg = "Usage: series <name>".to_string(),
+            ("series", name) => match self.data_series.iter().position(|s| s.name.eq_ignore_ascii_case(name)) {
+                Some(s) => self.select_serie(s),
+                None => self.status_msg = format!("No series named {}", name),
+            },
+            ("view", name) => self.set_view(name),
+            ("keys", "") => self.write_keys(KEYS_PATH),
+            ("keys", path) => self.write_keys(path),
+            ("export", "") => self.open_browser(BrowseAction::Export),
+            ("export", path) => self.export(path),
+            ("import", "") => if self.writable() { self.open_browser(BrowseAction::Import) },
+            ("import", path) => if self.writable() { self.import(path) },
+            ("rate", "") => self.status_msg = "Usage: rate <amount>/<day|week|month|x span> or rate off".to_string(),
+            ("rate", arg) => if self.writable() { self.set_rate_goal(arg) },
+            ("edit", _) => if self.writable() { self.edit_pending = true },
+            ("rule", "") => self.status_msg = format!("Rules: {}", self.data_series[self.selected_serie].rules.describe()),
+            ("rule", arg) => if self.writable() { self.set_rule(arg) },
+            ("cadence", "") => self.status_msg = "Usage: cadence <day|week|month|x span> or cadence off".to_string(),
+            ("cadence", arg) => if self.writable() { self.set_cadence(arg) },
+            ("average", "") => self.status_msg = "Usage: average <day|week|month|x span> or average off".to_string(),
+            ("average", arg) => if self.writable() { self.set_average(arg) },
+            ("merge", "") => self.status_msg = "Usage: merge <series> [both|mean|first]".to_string(),
+            ("merge", arg) => if self.writable() { self.merge_serie(arg) },
+            ("duplicate", _) => if self.writable() { self.duplicate_serie() },
+            ("rename", "") => self.status_msg = "Usage: rename <name>".to_string(),
+            ("rename", name) => if self.writable() { self.rename_serie(name) },
+            ("new", "") => self.status_msg = "Usage: new <name>".to_string(),
+            ("new", name) => if self.writable() { self.new_serie(name) },
+            ("group", arg) => if self.writable() {
+                let serie = &mut self.data_series[self.selected_serie];
+                serie.group = arg.to_string();
+                self.status_msg = match arg {
+                    "" => format!("{} is no longer grouped", serie.name),
+                    _ => format!("{} moved to group {}", serie.name, arg),
+                };
+            }
+            ("doctor", _) => self.run_doctor(),
+            ("theme", arg) => self.theme_command(arg),
+            ("move", "") => self.status_msg = format!("Usage: move <directory>, the data is in {}", current_dir_name()),
+            ("move", dir) => if self.writable() { self.relocate(dir) },
+            ("snapshot", arg) => self.snapshot_command(arg),
+            ("xformat", arg) => if self.writable() { self.set_x_format(arg) },
+            ("tags", arg) => if self.writable() { self.set_tags(arg) },
+            ("aggregate", arg) => if self.writable() { self.add_aggregate(arg) },
+            ("rollup", arg) => if self.writable() { self.add_rollup(arg) },
+            ("combine", arg) => if self.writable() { self.add_combination(arg) },
+            ("derive", arg) => if self.writable() { self.add_derived(arg) },
+            ("formula", arg) => if self.writable() { self.add_formula(arg) },
+            ("workspace", arg) => match arg.split_once(' ').map_or((arg, ""), |(action, path)| (action, path.trim())) {
+                ("save", "") => self.open_browser(BrowseAction::WorkspaceSave),
+                ("save", path) => self.save_workspace(path),
+                ("load", "") => if self.writable() { self.open_browser(BrowseAction::WorkspaceLoad) },
+                ("load", path) => if self.writable() { self.load_workspace(path) },
+                _ => self.status_msg = "Usage: workspace <save|load> [file.json]".to_string(),
+            },
+            (name, _) => self.status_msg = format!("Unknown command: {}", name),
+        }
+    }
+
+    fn read_snapshots() -> Result<SnapshotFile, Box<dyn Error>> {
+        match fs::read_to_string(SNAPSHOTS_PATH) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(SnapshotFile::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn write_snapshots(file: &SnapshotFile) -> Result<(), Box<dyn Error>> {
+        fs::write(SNAPSHOTS_PATH, serde_json::to_string_pretty(file)?)?;
+        Ok(())
+    }
+
+    // snapshot [name] keeps the selected series' points, list, diff <name>,
+    // restore <name> and delete <name> work on the kept ones
+    fn snapshot_command(&mut self, arg: &str) {
+        let (action, name) = arg.split_once(' ').map_or((arg, ""), |(action, name)| (action, name.trim()));
+        if self.demo {
+            self.status_msg = "Demo mode, snapshots are not kept".to_string();
+            return;
+        }
+        if matches!(action, "restore" | "delete") && !self.writable() {
+            return;
+        }
+        let mut file = match Self::read_snapshots() {
+            Ok(file) => file,
+            Err(e) => {
+                self.status_msg = format!("Could not read {}: {}", SNAPSHOTS_PATH, e);
+                return;
+            }
+        };
+        let serie = &self.data_series[self.selected_serie];
+        let found = file.snapshots.iter().position(|s| s.series == serie.name && s.name == name);
+
+        match (action, found) {
+            ("list", _) => {
+                let names: Vec<String> = file.snapshots.iter()
+                    .filter(|s| s.series == serie.name)
+                    .map(|s| format!("{} ({})", s.name, s.taken))
+                    .collect();
+                self.status_msg = match names.is_empty() {
+                    true => format!("No snapshots of {}, snapshot [name] takes one", serie.name),
+                    false => format!("Snapshots of {}: {}", serie.name, names.join(", ")),
+                };
+                return;
+            }
+            ("diff" | "restore" | "delete", _) if name.is_empty() => {
+                self.status_msg = format!("Usage: snapshot {} <name>", action);
+                return;
+            }
+            ("diff" | "restore" | "delete", None) => {
+                self.status_msg = format!("No snapshot {} of {}", name, serie.name);
+                return;
+            }
+            ("diff", Some(i)) => {
+                let mut old = DataSeries::default();
+                old.set_points(file.snapshots[i].points.clone());
+                let (removed, added) = serie.diff_points(&old.data);
+                let mut report = MergeReport { source: format!("Since snapshot {}", name), ..Default::default() };
+                if added + removed > 0 {
+                    report.updated.push((serie.name.clone(), added, removed));
+                }
+                self.merge_report = Some(report);
+                return;
+            }
+            ("restore", Some(i)) => {
+                // The points replaced are kept too, in case the restore was a mistake
+                let points = file.snapshots[i].points.clone();
+                let backup = format!("before {}", name);
+                file.snapshots.retain(|s| s.series != serie.name || s.name != backup);
+                file.snapshots.push(Snapshot {
+                    series: serie.name.clone(),
+                    name: backup.clone(),
+                    taken: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                    points: serie.json_points(),
+                });
+                let serie = &mut self.data_series[self.selected_serie];
+                serie.set_points(points);
+                self.undo.retain(|&(s, ..)| s != self.selected_serie);
+                self.status_msg = format!("Restored {} from {}, undo with snapshot restore {}", serie.name, name, backup);
+            }
+            ("delete", Some(i)) => {
+                file.snapshots.remove(i);
+                self.status_msg = format!("Deleted snapshot {}", name);
+            }
+            _ => {
+                // Anything else names a new snapshot, an existing one of the series is replaced
+                let name = match arg {
+                    "" => chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+                    _ => arg.to_string(),
+                };
+                if serie.aggregate.is_some() {
+                    self.status_msg = format!("{} is computed from other series, snapshot those", serie.name);
+                    return;
+                }
+                file.snapshots.retain(|s| s.series != serie.name || s.name != name);
+                file.snapshots.push(Snapshot {
+                    series: serie.name.clone(),
+                    name: name.clone(),
+                    taken: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                    points: serie.json_points(),
+                });
+                self.status_msg = format!("Took snapshot {} of {} with {} points", name, serie.name, serie.data.len());
+            }
+        }
+
+        if let Err(e) = Self::write_snapshots(&file) {
+            self.status_msg = format!("Could not write {}: {}", SNAPSHOTS_PATH, e);
+        }
+    }
+
+    fn run_doctor(&mut self) {
+        let unsorted = Self::unsorted_series("data.csv");
+        let now = chrono::Utc::now().timestamp() as f64;
+        let mut findings = Vec::new();
+        for (s, serie) in self.data_series.iter().enumerate() {
+            // Computed series follow their sources, archived ones are not tracked anymore
+            if serie.aggregate.is_some() || serie.archived {
+                continue;
+            }
+            for (issue, hint) in serie.health(now, unsorted.contains(&serie.name), self.config.precision) {
+                findings.push(Finding { serie: s, issue, hint });
+            }
+        }
+        match findings.is_empty() {
+            true => self.status_msg = format!("No issues in {} series", self.data_series.len()),
+            false => {
+                self.doctor = Some(findings);
+                self.doctor_idx = 0;
+            }
+        }
+    }
+
+    // Names of the series whose rows in the file do not go up in x
+    fn unsorted_series(path: &str) -> Vec<String> {
+        use std::collections::HashMap;
+        let Ok(mut rdr) = csv::ReaderBuilder::new().flexible(true).from_path(path) else { return Vec::new() };
+        let mut last: HashMap<String, f64> = HashMap::new();
+        let mut unsorted = Vec::new();
+        for record in rdr.records().flatten() {
+            let (Some(name), Some(Ok(x))) = (record.get(0), record.get(1).map(str::parse::<f64>)) else { continue };
+            if last.get(name).is_some_and(|&prev| x < prev) && !unsorted.iter().any(|n| n == name) {
+                unsorted.push(name.to_string());
+            }
+            last.insert(name.to_string(), x);
+        }
+        unsorted
+    }
+
+    // Empty goes back to plain numbers
+    fn set_x_format(&mut self, format: &str) {
+        let is_date = !format.contains("{}");
+        if is_date && !format.is_empty() {
+            // Invalid formats are refused here, not left to fall back on every label
+            let mut items = chrono::format::StrftimeItems::new(format);
+            if items.any(|i| i == chrono::format::Item::Error) || !format.contains('%') {
+                self.status_msg = "Use a date format like %b %d or text with {} for x, like #{}".to_string();
+                return;
+            }
+        }
+        let serie = &mut self.data_series[self.selected_serie];
+        serie.x_format = format.to_string();
+        self.status_msg = match format {
+            "" => format!("{} shows x as numbers", serie.name),
+            _ => match serie.data.last() {
+                Some(&(x, _)) => format!("{} shows x like {}", serie.name, serie.format_x(x, self.config.precision)),
+                None => format!("{} shows x as {}", serie.name, format),
+            },
+        };
+    }
+
+    fn set_view(&mut self, name: &str) {
+        let views = [
+            ViewMode::Graph, ViewMode::Table, ViewMode::Spectrum, ViewMode::Menu, ViewMode::Help,
+            ViewMode::Settings, ViewMode::Compare, ViewMode::Batch, ViewMode::Series, ViewMode::Trash,
+        ];
+        match views.into_iter().find(|v| v.label().split(' ').next() == Some(name)) {
+            Some(ViewMode::Batch) => if self.writable() { self.open_batch() },
+            Some(view) => self.mode = view,
+            None => {
+                let names: Vec<&str> = views.iter().filter_map(|v| v.label().split(' ').next()).collect();
+                self.status_msg = format!("Usage: view <{}>", names.join("|"));
+            }
+        }
+    }
+
+    // Selects the point nearest to x in the table and centers the chart on it
+    fn goto(&mut self, arg: &str) {
+        let Some(x) = parse_x(arg) else {
+            self.status_msg = format!("Not a number or date: {}", arg);
+            return;
+        };
+
+        let serie = &self.data_series[self.selected_serie];
+        if serie.data.is_empty() {
+            self.status_msg = "No points to jump to".to_string();
+            return;
+        }
+
+        let i = nearest_index(&serie.data, x);
+        let target = match self.table_all {
+            true => {
+                let rows = self.all_rows();
+                let xs: Vec<(f64, f64)> = rows.iter().map(|&(s, i)| self.data_series[s].data[i]).collect();
+                rows[nearest_index(&xs, x)]
+            }
+            false => (self.selected_serie, i),
+        };
+        self.table_state.select(self.table_rows().iter().position(|&p| p == target));
+
+        // Keep the current zoom, or show a quarter of the series
+        let half = match self.x_window {
+            Some((lo, hi)) => (hi - lo) / 2.0,
+            None => {
+                let span = serie.data[serie.data.len() - 1].0 - serie.data[0].0;
+                if span > 0.0 { span / 8.0 } else { 1.0 }
+            }
+        };
+        self.x_window = Some((x - half, x + half));
+        self.status_msg = format!("Jumped to x = {} (=: fit all)", serie.data[i].0);
+    }
+
+    // Adds a series summing or averaging the rest of the selected series' group
+    fn add_aggregate(&mut self, arg: &str) {
+        let mut words = arg.split_whitespace();
+        let Some(kind) = words.next().and_then(AggregateKind::parse) else {
+            self.status_msg = "Usage: aggregate sum|mean|min|max|last|count [week|month]".to_string();
+            return;
+        };
+        let per = match words.next() {
+            None => None,
+            Some("week") => Some(Period::Week),
+            Some("month") => Some(Period::Month),
+            Some(other) => {
+                self.status_msg = format!("Unknown period {}, use week or month", other);
+                return;
+            }
+        };
+
+        let group = self.data_series[self.selected_serie].group.clone();
+        if group.is_empty() {
+            self.status_msg = "Put the series in a group first (:group <name>)".to_string();
+            return;
+        }
+        let aggregate = Aggregate { kind, per, members: Vec::new(), formula: String::new() };
+        let name = format!("{} {}", group, aggregate.label());
+        if self.data_series.iter().any(|s| s.name == name) {
+            self.status_msg = format!("{} already exists", name);
+            return;
+        }
+
+        self.status_msg = format!("Created {}", name);
+        self.data_series.push(DataSeries {
+            name,
+            group,
+            unit: self.data_series[self.selected_serie].unit.clone(),
+            created_at: today(),
+            aggregate: Some(aggregate),
+            ..Default::default()
+        });
+        self.select_serie(self.data_series.len() - 1);
+        self.refresh_aggregates();
+    }
+
+    // Adds the selected series bucketed per week or month:
+    // rollup <week|month> <sum|mean|min|max|last|count>
+    fn add_rollup(&mut self, arg: &str) {
+        const USAGE: &str = "Usage: rollup week|month sum|mean|min|max|last|count";
+        let mut words = arg.split_whitespace();
+        let per = match words.next() {
+            Some("week") => Period::Week,
+            Some("month") => Period::Month,
+            _ => {
+                self.status_msg = USAGE.to_string();
+                return;
+            }
+        };
+        let Some(kind) = words.next().and_then(AggregateKind::parse) else {
+            self.status_msg = USAGE.to_string();
+            return;
+        };
+
+        let source = &self.data_series[self.selected_serie];
+        if source.aggregate.is_some() {
+            self.status_msg = "Roll up a series with points of its own".to_string();
+            return;
+        }
+        let aggregate = Aggregate { kind, per: Some(per), members: vec![(source.name.clone(), 1.0)], formula: String::new() };
+        let name = format!("{} {}", source.name, aggregate.label());
+        if self.data_series.iter().any(|s| s.name == name) {
+            self.status_msg = format!("{} already exists", name);
+            return;
+        }
+
+        self.status_msg = format!("Created {}", name);
+        self.data_series.push(DataSeries {
+            name,
+            group: source.group.clone(),
+            // A count has no unit
+            unit: match kind {
+                AggregateKind::Count => String::new(),
+                _ => source.unit.clone(),
+            },
+            created_at: today(),
+            aggregate: Some(aggregate),
+            ..Default::default()
+        });
+        self.select_serie(self.data_series.len() - 1);
+        self.refresh_aggregates();
+    }
+
+    // Adds a weighted sum or mean of named series:
+    // combine <sum|mean> <name> = <series>[*weight], <series>[*weight], ...
+    fn add_combination(&mut self, arg: &str) {
+        const USAGE: &str = "Usage: combine sum|mean <name> = <series>[*weight], ...";
+        let Some((head, list)) = arg.split_once('=') else {
+            self.status_msg = USAGE.to_string();
+            return;
+        };
+        let (kind, name) = match head.trim().split_once(' ') {
+            Some(("sum", name)) => (AggregateKind::Sum, name.trim()),
+            Some(("mean", name)) => (AggregateKind::Mean, name.trim()),
+            _ => {
+                self.status_msg = USAGE.to_string();
+                return;
+            }
+        };
+
+        let mut members = Vec::new();
+        for member in list.split(',').map(str::trim).filter(|m| !m.is_empty()) {
+            let (serie, weight) = match member.rsplit_once('*') {
+                Some((serie, weight)) => match weight.trim().parse::<f64>() {
+                    Ok(weight) => (serie.trim(), weight),
+                    Err(_) => {
+                        self.status_msg = format!("Not a weight: {}", weight.trim());
+                        return;
+                    }
+                },
+                None => (member, 1.0),
+            };
+            if !self.data_series.iter().any(|s| s.name == serie && s.aggregate.is_none()) {
+                self.status_msg = format!("No series named {}", serie);
+                return;
+            }
+            members.push((serie.to_string(), weight));
+        }
+        if members.is_empty() || name.is_empty() {
+            self.status_msg = USAGE.to_string();
+            return;
+        }
+        if self.data_series.iter().any(|s| s.name == name) {
+            self.status_msg = format!("There already is a series named {}", name);
+            return;
+        }
+
+        self.status_msg = format!("Created {} from {} series", name, members.len());
+        self.data_series.push(DataSeries {
+            name: name.to_string(),
+            created_at: today(),
+            aggregate: Some(Aggregate { kind, per: None, members, formula: String::new() }),
+            ..Default::default()
+        });
+        self.select_serie(self.data_series.len() - 1);
+        self.refresh_aggregates();
+    }
+
+    // Adds a series computed from two others, interpolated where their x differ:
+    // derive <name> = <a> +|-|/ <b>
+    fn add_derived(&mut self, arg: &str) {
+        const USAGE: &str = "Usage: derive <name> = <series> +|-|/ <series>";
+        let Some((name, expression)) = arg.split_once('=').map(|(n, e)| (n.trim(), e.trim())) else {
+            self.status_msg = USAGE.to_string();
+            return;
+        };
+        // Spaces around the operator, names may contain dashes
+        let Some((a, kind, b)) = [(" + ", AggregateKind::Plus), (" - ", AggregateKind::Minus), (" / ", AggregateKind::Ratio)]
+            .into_iter()
+            .find_map(|(op, kind)| expression.split_once(op).map(|(a, b)| (a.trim(), kind, b.trim())))
+        else {
+            self.status_msg = USAGE.to_string();
+            return;
+        };
+        if name.is_empty() {
+            self.status_msg = USAGE.to_string();
+            return;
+        }
+        for serie in [a, b] {
+            if !self.data_series.iter().any(|s| s.name == serie && s.aggregate.is_none()) {
+                self.status_msg = format!("No series named {}", serie);
+                return;
+            }
+        }
+        if self.data_series.iter().any(|s| s.name == name) {
+            self.status_msg = format!("There already is a series named {}", name);
+            return;
+        }
+
+        let unit = match kind {
+            AggregateKind::Ratio => String::new(),
+            _ => self.data_series.iter().find(|s| s.name == a).map(|s| s.unit.clone()).unwrap_or_default(),
+        };
+        self.status_msg = format!("Created {} = {} {} {}", name, a, kind.name(), b);
+        self.data_series.push(DataSeries {
+            name: name.to_string(),
+            unit,
+            created_at: today(),
+            aggregate: Some(Aggregate {
+                kind,
+                per: None,
+                members: vec![(a.to_string(), 1.0), (b.to_string(), 1.0)],
+                formula: String::new(),
+            }),
+            ..Default::default()
+        });
+        self.select_serie(self.data_series.len() - 1);
+        self.refresh_aggregates();
+    }
+
+    // Adds a series computed by an expression over others, recomputed with them:
+    // formula <name> = [y =] <expression>, like formula weight lb = 2.2 * weight.y
+    fn add_formula(&mut self, arg: &str) {
+        const USAGE: &str = "Usage: formula <name> = <expression with series.y>";
+        let Some((name, text)) = arg.split_once('=').map(|(n, t)| (n.trim(), t.trim())) else {
+            self.status_msg = USAGE.to_string();
+            return;
+        };
+        let text = text.strip_prefix("y =").or_else(|| text.strip_prefix("y=")).unwrap_or(text).trim();
+        if name.is_empty() || text.is_empty() {
+            self.status_msg = USAGE.to_string();
+            return;
+        }
+        let expr = match formula::parse(text) {
+            Ok(expr) => expr,
+            Err(e) => {
+                self.status_msg = format!("Formula: {}", e);
+                return;
+            }
+        };
+        let refs = expr.refs();
+        if refs.is_empty() {
+            self.status_msg = "Refer to a series, like weight.y".to_string();
+            return;
+        }
+        if let Some(missing) = refs.iter().find(|r| !self.data_series.iter().any(|s| &s.name == *r && s.aggregate.is_none())) {
+            self.status_msg = format!("No series named {}", missing);
+            return;
+        }
+        if self.data_series.iter().any(|s| s.name == name) {
+            self.status_msg = format!("There already is a series named {}", name);
+            return;
+        }
+
+        self.status_msg = format!("Created {} = {}", name, expr);
+        self.data_series.push(DataSeries {
+            name: name.to_string(),
+            created_at: today(),
+            aggregate: Some(Aggregate {
+                kind: AggregateKind::Formula,
+                per: None,
+                members: refs.into_iter().map(|r| (r, 1.0)).collect(),
+                formula: expr.to_string(),
+            }),
+            ..Default::default()
+        });
+        self.select_serie(self.data_series.len() - 1);
+        self.refresh_aggregates();
+    }
+
+    // Recomputes the points of aggregate series from their members
+    fn refresh_aggregates(&mut self) {
+        for i in 0..self.data_series.len() {
+            let Some(aggregate) = self.data_series[i].aggregate.clone() else { continue };
+            let members: Vec<(&DataSeries, f64)> = match aggregate.members.is_empty() {
+                true => self.data_series.iter()
+                    .filter(|s| s.aggregate.is_none() && s.group == self.data_series[i].group)
+                    .map(|s| (s, 1.0))
+                    .collect(),
+                false => aggregate.members.iter()
+                    .filter_map(|(name, weight)| {
+                        let serie = self.data_series.iter().find(|s| &s.name == name && s.aggregate.is_none())?;
+                        Some((serie, *weight))
+                    })
+                    .collect(),
+            };
+            self.data_series[i].data = aggregate.compute(&members);
+            self.data_series[i].points_changed();
+        }
+        self.aggregate_inputs = self.aggregate_inputs();
+    }
+
+    // Changes with the points, names and groups of the series computed series
+    // are made of, and with the set of computed series
+    fn aggregate_inputs(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for serie in &self.data_series {
+            (&serie.name, &serie.group, serie.aggregate.is_some()).hash(&mut hasher);
+            if serie.aggregate.is_none() {
+                serie.revision.hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+
+    // Starts a rate goal at the point selected in the table, or the latest one
+    fn set_rate_goal(&mut self, arg: &str) {
+        let selected = self.table_state.selected()
+            .and_then(|row| self.table_point(row))
+            .filter(|&(s, _)| s == self.selected_serie);
+        let serie = &mut self.data_series[self.selected_serie];
+
+        if arg == "off" {
+            serie.rate_goal = None;
+            self.status_msg = "Rate goal removed".to_string();
+            return;
+        }
+
+        let Some((rate, per)) = arg.split_once('/') else {
+            self.status_msg = "Usage: rate <amount>/<day|week|month|x span> or rate off".to_string();
+            return;
+        };
+        let (rate, per) = (rate.trim(), per.trim());
+        let Ok(rate) = rate.parse::<f64>() else {
+            self.status_msg = format!("Not a number: {}", rate);
+            return;
+        };
+        if parse_period(per, true).is_none() {
+            self.status_msg = format!("Not a period: {}", per);
+            return;
+        }
+        let Some(i) = selected.map(|(_, i)| i).or(serie.data.len().checked_sub(1)) else {
+            self.status_msg = "Add a point to start the goal from".to_string();
+            return;
+        };
+
+        let start = serie.data[i];
+        serie.rate_goal = Some(RateGoal { rate, per: per.to_string(), start });
+        self.status_msg = format!("Rate goal {}/{} from x = {}", rate, per, start.0);
+    }
+
+    fn set_cadence(&mut self, arg: &str) {
+        let serie = &mut self.data_series[self.selected_serie];
+        if arg == "off" {
+            serie.cadence = None;
+            self.status_msg = format!("{} has no cadence", serie.name);
+            return;
+        }
+        if parse_period(arg, true).is_none() {
+            self.status_msg = format!("Not a period: {}", arg);
+            return;
+        }
+        serie.cadence = Some(arg.to_string());
+        self.status_msg = format!("{} is expected every {}", serie.name, arg);
+    }
+
+    fn set_average(&mut self, arg: &str) {
+        let serie = &mut self.data_series[self.selected_serie];
+        if arg == "off" {
+            serie.average = None;
+            self.status_msg = format!("{} has no moving average", serie.name);
+            return;
+        }
+        if parse_period(arg, true).is_none() {
+            self.status_msg = format!("Not a period: {}", arg);
+            return;
+        }
+        serie.average = Some(arg.to_string());
+        self.status_msg = format!("Moving average of {} over {}", serie.name, arg);
+        self.hidden.retain(|h| h != "average");
+    }
+
+    // Shows or hides the moving average, a series without one gets the default window
+    fn toggle_average(&mut self) {
+        let serie = &mut self.data_series[self.selected_serie];
+        let hidden = self.hidden.iter().position(|h| h == "average");
+        match (&serie.average, hidden) {
+            (None, _) => {
+                serie.average = Some(serie.default_average().to_string());
+                self.hidden.retain(|h| h != "average");
+            }
+            (Some(_), Some(i)) => {
+                self.hidden.remove(i);
+            }
+            (Some(_), None) => {
+                self.hidden.push("average".to_string());
+                self.status_msg = "Moving average hidden".to_string();
+                return;
+            }
+        }
+        self.status_msg = format!("Moving average over {} (:average changes it)", serie.average.as_deref().unwrap_or_default());
+    }
+
+    // Shows or hides the trend line, with its slope and intercept in the status bar
+    fn toggle_trend(&mut self) {
+        if self.line_shown("trend") {
+            self.data_series[self.selected_serie].trend_line = false;
+            self.status_msg = "Trend line hidden".to_string();
+            return;
+        }
+        self.data_series[self.selected_serie].trend_line = true;
+        self.hidden.retain(|h| h != "trend");
+
+        let serie = &self.data_series[self.selected_serie];
+        let precision = serie.precision(self.config.precision);
+        let unit = match serie.unit.is_empty() {
+            true => String::new(),
+            false => format!(" {}", serie.unit),
+        };
+        let signed = |v: f64| match v >= 0.0 {
+            true => format!("+{}", format_value(v, precision)),
+            false => format_value(v, precision),
+        };
+        self.status_msg = match serie.trend_fit(self.x_window) {
+            // Per day for dates, with the intercept at the first point in view
+            Some((slope, intercept)) => match serie.data[0].0 >= DATE_MIN {
+                true => {
+                    let x = self.x_window.map_or(serie.data[0].0, |(x0, _)| x0.max(serie.data[0].0));
+                    format!("Trend {}{}/day, {}{} at {}", signed(slope * 86400.0), unit,
+                        format_value(slope * x + intercept, precision), unit, serie.describe_x(x, precision))
+                }
+                false => format!("Trend y = {} x {} {}  ({}{} per x)", format_value(slope, precision),
+                    if intercept < 0.0 { "-" } else { "+" }, format_value(intercept.abs(), precision),
+                    signed(slope), unit),
+            },
+            None => "Trend line needs two points with different x in view".to_string(),
+        };
+    }
+
+    // Shows one more of OVERLAY_LINES than are shown now, counted from the
+    // first, or none once they all are
+    fn cycle_overlays(&mut self) {
+        let shown = OVERLAY_LINES.iter().take_while(|line| self.line_shown(line)).count();
+        let next = (shown + 1) % (OVERLAY_LINES.len() + 1);
+        let serie = &mut self.data_series[self.selected_serie];
+
+        for &line in &OVERLAY_LINES[..next] {
+            match line {
+                "average" if serie.average.is_none() => serie.average = Some(serie.default_average().to_string()),
+                "trend" => serie.trend_line = true,
+                _ => {}
+            }
+        }
+        self.hidden.retain(|h| !OVERLAY_LINES.contains(&h.as_str()));
+        self.hidden.extend(OVERLAY_LINES[next..].iter().map(|line| line.to_string()));
+        self.status_msg = OVERLAY_LINES[..next].iter()
+            .fold("Points".to_string(), |msg, line| format!("{} + {}", msg, line));
+    }
+
+    fn new_serie(&mut self, name: &str) {
+        // Names group the points in data.csv
+        if self.data_series.iter().any(|s| s.name == name) {
+            self.status_msg = format!("There already is a series named {}", name);
+            return;
+        }
+        self.data_series.push(DataSeries { name: name.to_string(), created_at: today(), ..Default::default() });
+        self.select_serie(self.data_series.len() - 1);
+        self.status_msg = format!("Created series {}", name);
+    }
+
+    fn rename_serie(&mut self, name: &str) {
+        let old = self.data_series[self.selected_serie].name.clone();
+        if old == name {
+            return;
+        }
+        if self.data_series.iter().any(|s| s.name == name) {
+            self.status_msg = format!("There already is a series named {}", name);
+            return;
+        }
+
+        self.data_series[self.selected_serie].name = name.to_string();
+        // Combinations and formulas refer to their members by name
+        for aggregate in self.data_series.iter_mut().filter_map(|s| s.aggregate.as_mut()) {
+            for member in aggregate.members.iter_mut().filter(|(member, _)| *member == old) {
+                member.0 = name.to_string();
+            }
+            if let Ok(mut expr) = formula::parse(&aggregate.formula)
+                && aggregate.kind == AggregateKind::Formula {
+                expr.rename(&old, name);
+                aggregate.formula = expr.to_string();
+            }
+        }
+        // Snapshots are kept by series name
+        if let Ok(mut file) = Self::read_snapshots()
+            && file.snapshots.iter().any(|s| s.series == old) {
+            for snapshot in file.snapshots.iter_mut().filter(|s| s.series == old) {
+                snapshot.series = name.to_string();
+            }
+            let _ = Self::write_snapshots(&file);
+        }
+        if let Some(overlaid) = self.overlay.iter_mut().find(|o| **o == old) {
+            *overlaid = name.to_string();
+        }
+        if let Some(kpi) = self.config.kpis.iter_mut().find(|k| **k == old) {
+            *kpi = name.to_string();
+            let _ = self.save_config();
+        }
+        self.status_msg = format!("Renamed {} to {}", old, name);
+    }
+
+    // Moves the points of another series into the selected one and deletes it:
+    // merge <series> [both|mean|first], the policy deciding points at an x both have
+    fn merge_serie(&mut self, arg: &str) {
+        let (name, policy) = match arg.rsplit_once(' ') {
+            Some((name, policy @ ("both" | "mean" | "first"))) => (name.trim(), policy),
+            _ => (arg, "both"),
+        };
+        let Some(o) = self.data_series.iter().position(|s| s.name == name) else {
+            self.status_msg = format!("No series named {}", name);
+            return;
+        };
+        if o == self.selected_serie {
+            self.status_msg = "Pick another series to merge into this one".to_string();
+            return;
+        }
+        self.merge_into(o, policy);
+    }
+
+    fn merge_into(&mut self, o: usize, policy: &str) {
+        if self.data_series[o].aggregate.is_some() || self.data_series[self.selected_serie].aggregate.is_some() {
+            self.status_msg = "Computed series cannot be merged".to_string();
+            return;
+        }
+
+        let other = &self.data_series[o];
+        let name = other.name.clone();
+        let points: Vec<(f64, f64, PointMeta)> = other.data.iter().enumerate()
+            .map(|(i, &(x, y))| (x, y, other.point_meta.get(i).cloned().unwrap_or_default()))
+            .collect();
+        let serie = &mut self.data_series[self.selected_serie];
+        let (mut added, mut shared) = (0, 0);
+        for (x, y, meta) in points {
+            match (serie.data.iter().position(|p| p.0 == x), policy) {
+                (Some(i), "mean") => {
+                    let own = serie.data[i].1;
+                    // Halving is exact, so the mean keeps the one extra decimal it needs
+                    serie.data[i].1 = round_like(own + y, &[own, y]) / 2.0;
+                    serie.points_changed();
+                    shared += 1;
+                }
+                (Some(_), "first") => shared += 1,
+                (existing, _) => {
+                    serie.insert_point(x, y, meta);
+                    shared += existing.is_some() as usize;
+                    added += 1;
+                }
+            }
+        }
+
+        let target = serie.name.clone();
+        self.delete_series(o);
+        self.status_msg = format!("Merged {} into {}: {} points added, {} at a shared x ({})", name, target, added, shared, policy);
+    }
+
+    // Opens the command line with the name of the selected series to edit
+    fn start_rename(&mut self) {
+        self.command = Some(format!("rename {}", self.data_series[self.selected_serie].name));
+    }
+
+    // Copies the selected series with all its points, computed points become plain ones
+    fn duplicate_serie(&mut self) {
+        let mut serie = self.data_series[self.selected_serie].clone();
+        serie.aggregate = None;
+        serie.created_at = today();
+
+        // Keep names unique, they group the points in data.csv
+        let base = format!("{} (copy)", serie.name);
+        serie.name = base.clone();
+        let mut n = 2;
+        while self.data_series.iter().any(|s| s.name == serie.name) {
+            serie.name = format!("{} {}", base, n);
+            n += 1;
+        }
+
+        self.status_msg = format!("Created series {}", serie.name);
+        self.data_series.push(serie);
+        self.select_serie(self.data_series.len() - 1);
+    }
+
+    // Favorite series in the order 1-9 select them
+    fn favorites(&self) -> Vec<usize> {
+        (0..self.data_series.len())
+            .filter(|&s| self.data_series[s].favorite && !self.data_series[s].archived)
+            .collect()
+    }
+
+    // n counts from 1, like the key selecting it
+    fn select_favorite(&mut self, n: usize) {
+        let Some(&s) = self.favorites().get(n - 1) else {
+            self.status_msg = format!("No favorite series {} (p in the series list)", n);
+            return;
+        };
+        self.select_serie(s);
+        if let Some(i) = self.series_list().iter().position(|e| *e == ListEntry::Serie(s)) {
+            self.series_idx = i;
+        }
+        self.status_msg = format!("Series {}", self.data_series[s].name);
+    }
+
+    // Archived series are skipped unless shown, or when every series is archived
+    fn cycle_serie(&mut self, step: isize) {
+        let len = self.data_series.len() as isize;
+        let next = (1..=len)
+            .map(|k| (self.selected_serie as isize + step * k).rem_euclid(len) as usize)
+            .find(|&s| self.show_archived || !self.data_series[s].archived)
+            .unwrap_or(self.selected_serie);
+        self.select_serie(next);
+        self.status_msg = format!("Series {}", self.data_series[self.selected_serie].name);
+    }
+
+    fn select_serie(&mut self, s: usize) {
+        self.selected_serie = s;
+        self.table_state.select(None);
+    }
+
+    // Swaps the series with the previous or next one of its group, so it
+    // moves one row in the series list
+    fn move_serie(&mut self, s: usize, up: bool) {
+        let group = &self.data_series[s].group;
+        let neighbour = match up {
+            true => (0..s).rev().find(|&i| &self.data_series[i].group == group),
+            false => (s + 1..self.data_series.len()).find(|&i| &self.data_series[i].group == group),
+        };
+        let Some(other) = neighbour else { return };
+
+        self.data_series.swap(s, other);
+        if self.selected_serie == s {
+            self.selected_serie = other;
+        } else if self.selected_serie == other {
+            self.selected_serie = s;
+        }
+        // Undo refers to series by index
+        self.undo.clear();
+        if let Some(i) = self.series_list().iter().position(|e| *e == ListEntry::Serie(other)) {
+            self.series_idx = i;
+        }
+    }
+
+    // Adds the series to the ones drawn over the selected series' graph, or takes it off
+    fn toggle_overlay(&mut self, s: usize) {
+        let name = self.data_series[s].name.clone();
+        self.status_msg = match self.overlay.iter().position(|o| *o == name) {
+            Some(i) => {
+                self.overlay.remove(i);
+                format!("{} left the overlay", name)
+            }
+            None if s == self.selected_serie => format!("{} is the graph's own series", name),
+            None => {
+                self.overlay.push(name.clone());
+                format!("Overlaying {} on the graph (O: clear)", name)
+            }
+        };
+    }
+
+    // Colors of the configured theme, the built-in ones when it is unset or
+    // cannot be read. False with the reason in the status bar on failure
+    fn load_theme(&mut self) -> bool {
+        if self.config.theme.is_empty() {
+            self.theme = Theme::default();
+            return true;
+        }
+        match Theme::load(&self.config.theme) {
+            Ok(theme) => {
+                self.theme = theme;
+                true
+            }
+            Err(e) => {
+                self.theme = Theme::default();
+                self.status_msg = format!("Could not load theme {}: {}", self.config.theme, e);
+                false
+            }
+        }
+    }
+
+    // Picks up edits to the theme's file without a restart
+    fn reload_theme(&mut self) {
+        if self.config.theme.is_empty() {
+            self.status_msg = format!("Using the default theme, :theme <name> picks one from {}/", THEMES_DIR);
+        } else if self.load_theme() {
+            self.status_msg = format!("Reloaded theme {}", self.config.theme);
+        }
+    }
+
+    fn theme_command(&mut self, arg: &str) {
+        match arg.split_once(' ').map_or((arg, ""), |(a, b)| (a, b.trim())) {
+            ("", _) => {
+                let names = Theme::list();
+                self.status_msg = format!(
+                    "Theme {}, in {}/: {}",
+                    if self.config.theme.is_empty() { "default" } else { &self.config.theme },
+                    THEMES_DIR,
+                    if names.is_empty() { "none".to_string() } else { names.join(", ") },
+                );
+            }
+            ("export", "") => self.status_msg = "Usage: theme export <name>".to_string(),
+            ("export", name) => {
+                self.status_msg = match self.theme.save(name) {
+                    Ok(path) => format!("Wrote {}", path),
+                    Err(e) => format!("Could not write theme {}: {}", name, e),
+                };
+            }
+            _ => {
+                let previous = std::mem::replace(&mut self.config.theme, if arg == "default" { String::new() } else { arg.to_string() });
+                if !self.load_theme() {
+                    self.config.theme = previous;
+                    self.load_theme();
+                    return;
+                }
+                self.status_msg = match self.save_config() {
+                    Ok(()) => format!("Theme {}, saved {}", arg, CONFIG_PATH),
+                    Err(e) => format!("Could not write {}: {}", CONFIG_PATH, e),
+                };
+            }
+        }
+    }
+
+    fn serie_color(&self, s: usize) -> Color {
+        self.data_series[s].color.unwrap_or(self.theme.series_color(s))
+    }
+
+    // Steps the series to the next palette color, which is then kept in series.toml
+    fn cycle_color(&mut self, s: usize) {
+        let color = cycle(&self.theme.series, self.serie_color(s), 1);
+        self.data_series[s].color = Some(color);
+        self.status_msg = format!("{} is now {}", self.data_series[s].name, color);
+    }
+
+    fn open_browser(&mut self, action: BrowseAction) {
+        let dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        match FileBrowser::open(action, dir) {
+            Ok(browser) => self.browser = Some(browser),
+            Err(e) => self.status_msg = format!("Could not list the directory: {}", e),
+        }
+    }
+
+    fn handle_browser_input(&mut self, key: KeyCode) {
+        let Some(browser) = &mut self.browser else { return };
+        let visible: Vec<(String, bool)> = browser.visible().into_iter().cloned().collect();
+
+        let picked = match key {
+            KeyCode::Up => {
+                browser.idx = browser.idx.saturating_sub(1);
+                None
+            }
+            KeyCode::Down => {
+                browser.idx = (browser.idx + 1).min(visible.len().saturating_sub(1));
+                None
+            }
+            KeyCode::Char(c) => {
+                browser.filter.push(c);
+                browser.idx = 0;
+                None
+            }
+            KeyCode::Backspace if !browser.filter.is_empty() => {
+                browser.filter.pop();
+                browser.idx = 0;
+                None
+            }
+            KeyCode::Backspace | KeyCode::Left => browser.dir.parent().map(|parent| (parent.to_path_buf(), true)),
+            KeyCode::Enter => match visible.get(browser.idx) {
+                Some((name, _)) if name == ".." => browser.dir.parent().map(|parent| (parent.to_path_buf(), true)),
+                Some((name, dir)) => Some((browser.dir.join(name), *dir)),
+                None if browser.action.saves() && !browser.filter.is_empty() => Some((browser.dir.join(&browser.filter), false)),
+                None => None,
+            },
+            KeyCode::Esc => {
+                self.browser = None;
+                return;
+            }
+            _ => None,
+        };
+
+        match picked {
+            Some((dir, true)) => {
+                let previous = std::mem::replace(&mut browser.dir, dir);
+                if let Err(e) = browser.read_dir() {
+                    browser.dir = previous;
+                    self.status_msg = format!("Could not open the directory: {}", e);
+                }
+            }
+            Some((file, false)) => {
+                let action = browser.action;
+                self.browser = None;
+                let path = file.to_string_lossy().to_string();
+                match action {
+                    // Straight to the file, the path is not parsed for a column mapping
+                    BrowseAction::Import => self.import_file(&path, ""),
+                    BrowseAction::Export => self.export(&path),
+                    BrowseAction::WorkspaceSave => self.save_workspace(&path),
+                    BrowseAction::WorkspaceLoad => self.load_workspace(&path),
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn handle_template_input(&mut self, key: KeyCode) {
+        let Some(idx) = self.template_idx else { return };
+        let templates = self.config.templates();
+
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => self.template_idx = Some(idx.checked_sub(1).unwrap_or(templates.len() - 1)),
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => self.template_idx = Some((idx + 1) % templates.len()),
+            KeyCode::Enter => {
+                self.create_from_template(&templates[idx]);
+                self.template_idx = None;
+            }
+            KeyCode::Esc => self.template_idx = None,
+            _ => {}
+        }
+    }
+
+    fn create_from_template(&mut self, template: &SeriesTemplate) {
+        let mut serie = DataSeries::from_template(template);
+
+        // Keep names unique, they group the points in data.csv
+        let mut n = 2;
+        while self.data_series.iter().any(|s| s.name == serie.name) {
+            serie.name = format!("{} {}", template.name, n);
+            n += 1;
+        }
+
+        self.status_msg = format!("Created series {} from template", serie.name);
+        self.data_series.push(serie);
+        self.select_serie(self.data_series.len() - 1);
+    }
+
+    fn toggle_recording(&mut self) {
+        match self.recording.take() {
+            Some(keys) => {
+                self.status_msg = format!("Recorded macro of {} keys (@: replay)", keys.len());
+                self.macro_keys = keys;
+            }
+            None => {
+                self.recording = Some(Vec::new());
+                self.status_msg = "Recording macro (Q: stop)".to_string();
+            }
+        }
+    }
+
+    fn replay_macro(&mut self) {
+        if self.recording.is_some() {
+            return;
+        }
+        if self.macro_keys.is_empty() {
+            self.status_msg = "No macro recorded (Q: record)".to_string();
+            return;
+        }
+
+        for key in self.macro_keys.clone() {
+            if self.exit {
+                break;
+            }
+            self.handle_key(key);
+            self.advance_tour();
+        }
+    }
+
+    fn select_previous(&mut self) {
+        let len = self.table_len();
+        if len == 0 {
+            return;
+        }
+        let i = match self.table_state.selected() {
+            Some(i) => {
+                if i >= len - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
         };
-        self.draw_input_box(frame, input_chunks[0], self.input_x.clone(), format!(" X "), x_style);
+        self.table_state.select(Some(i));
+    }
 
-        // Y
-        let y_style = match (&self.input_mode, &self.input_field) {
-            (InputMode::Insert, InputField::Y) => Style::default().fg(Color::Yellow),
-            _ => Style::default(),
+    fn select_next(&mut self) {
+        let len = self.table_len();
+        if len == 0 {
+            return;
+        }
+        let i = match self.table_state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    len - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
         };
-        self.draw_input_box(frame, input_chunks[1], self.input_y.clone(), format!(" Y "), y_style);
+        self.table_state.select(Some(i));
+    }
 
-        // Status
-        let status = Paragraph::new(self.status_msg.clone())
-            .block(Block::bordered().title(" Status ").padding(Padding::left(1)));
-        frame.render_widget(status, input_chunks[2]);
+    // Configured chart marker, see fallback
+    fn marker(&self) -> symbols::Marker {
+        self.fallback(self.config.marker.symbol())
     }
 
-    fn draw_input_box(&mut self, frame: &mut Frame, area: Rect, content: String, title: String, style: Style) {
-        let input_box = Paragraph::new(content)
-            .block(Block::bordered().title(title).padding(Padding::left(1)))
-            .style(style);
-            
-        frame.render_widget(input_box, area);
+    fn serie_marker(&self, s: usize) -> symbols::Marker {
+        self.fallback(self.data_series[s].marker.unwrap_or(self.config.marker).symbol())
     }
 
-    fn draw_graph(&mut self, frame: &mut Frame, area: Rect) {
+    // Plain dots when the terminal is unlikely to have the marker's glyphs
+    fn fallback(&self, marker: symbols::Marker) -> symbols::Marker {
+        match (self.unicode, marker) {
+            (false, symbols::Marker::Braille | symbols::Marker::HalfBlock) => symbols::Marker::Dot,
+            _ => marker,
+        }
+    }
+
+    // Moves the y of point i of series s by `step`, remembering the old y for undo
+    fn nudge(&mut self, s: usize, i: usize, step: f64) {
+        if self.data_series[s].aggregate.is_some() {
+            self.status_msg = "This series is computed from its group".to_string();
+            return;
+        }
+        let (x, y) = self.data_series[s].data[i];
+        let nudged = round_like(y + step, &[y, step]);
+        self.undo.push((s, x, nudged, y));
+        self.data_series[s].data[i].1 = nudged;
+        self.data_series[s].points_changed();
+    }
+
+    fn nudge_selected(&mut self, step: f64) {
+        if let Some((s, i)) = self.table_state.selected().and_then(|row| self.table_point(row)) {
+            self.nudge(s, i, step);
+        }
+    }
+
+    // Restores the y of the last nudged point, if it still exists
+    fn undo_nudge(&mut self) {
+        let Some((s, x, nudged, y)) = self.undo.pop() else {
+            self.status_msg = "Nothing to undo".to_string();
+            return;
+        };
+        let point = self.data_series.get_mut(s)
+            .and_then(|serie| {
+                serie.points_changed();
+                // Matched by y too, other points may share the x
+                serie.data.iter_mut().find(|p| **p == (x, nudged))
+            });
+        self.status_msg = match point {
+            Some(point) => {
+                point.1 = y;
+                format!("Undone, y = {} at x = {}", format_value(y, self.config.precision), x)
+            }
+            None => "The nudged point no longer exists".to_string(),
+        };
+    }
+
+    fn cycle_confirm_idx(&mut self) {
+        self.confirm_idx = (self.confirm_idx + 1) % DELETE_CHOICES.len();
+    }
+
+    fn delete(&mut self, scope: DeleteScope) {
+        let selected = self.table_state.selected().and_then(|row| self.table_point(row));
+        // A series without points can still be deleted as a whole
+        let Some((s, i)) = selected else {
+            if let DeleteScope::Series = scope {
+                self.delete_series(self.selected_serie);
+            }
+            return;
+        };
+        // Its points come from other series, only the series as a whole can go
+        if self.data_series[s].aggregate.is_some() && !matches!(scope, DeleteScope::Series) {
+            self.status_msg = "This series is computed from its group".to_string();
+            return;
+        }
+        let serie = &mut self.data_series[s];
+        match scope {
+            DeleteScope::Point => {
+                serie.trash_point(i);
+                self.status_msg = "Moved the point to the trash (x: trash)".to_string();
+            }
+            DeleteScope::SameX => {
+                let x = serie.data[i].0;
+                let mut removed = 0;
+                while let Some(j) = serie.data.iter().position(|p| p.0 == x) {
+                    serie.trash_point(j);
+                    removed += 1;
+                }
+                self.status_msg = format!("Moved {} points at x = {} to the trash (x: trash)", removed, x);
+            }
+            DeleteScope::Series => {
+                self.delete_series(s);
+                self.table_state.select(None);
+            }
+        }
+    }
+
+    // There is always a series to select, the last one is replaced by an empty one
+    fn delete_series(&mut self, s: usize) {
+        let serie = self.data_series.remove(s);
+        self.overlay.retain(|o| *o != serie.name);
+        self.status_msg = format!("Deleted series {}", serie.name);
+        if self.data_series.is_empty() {
+            self.data_series.push(DataSeries::new());
+        }
+        if self.selected_serie > s || self.selected_serie >= self.data_series.len() {
+            self.selected_serie = self.selected_serie.saturating_sub(1);
+        }
+        // Undo refers to series by index
+        self.undo.clear();
+    }
+
+    fn handle_table_input(&mut self, key: KeyCode) {
+        // Any key closes the stats popup
+        if self.point_stats {
+            self.point_stats = false;
+            return;
+        }
+
+        match self.confirm_delete {
+            false => {
+                if matches!(key, KeyCode::Char('d' | '+' | '-' | 'u' | 'p' | 'T' | 'n' | 'r')) && !self.writable() {
+                    return;
+                }
+                match key {
+                    KeyCode::Char('q') => self.exit = true,
+                    KeyCode::Char('g') => self.mode = ViewMode::Graph,
+                    KeyCode::Char('m') => self.mode = ViewMode::Menu,
+                    KeyCode::Char('h') => self.mode = ViewMode::Help,
+                    KeyCode::Char('f') => self.mode = ViewMode::Spectrum,
+                    KeyCode::Char('c') => self.mode = ViewMode::Compare,
+                    KeyCode::Char('s') => self.mode = ViewMode::Settings,
+                    KeyCode::Char('S') => self.mode = ViewMode::Series,
+                    KeyCode::Up | KeyCode::Char('k') => self.select_next(),
+                    KeyCode::Down | KeyCode::Char('j') => self.select_previous(), 
+                    KeyCode::Char('d') => self.confirm_delete = true,
+                    KeyCode::Char('+') => self.nudge_selected(self.config.nudge_step),
+                    KeyCode::Char('-') => self.nudge_selected(-self.config.nudge_step),
+                    KeyCode::Char('u') => self.undo_nudge(),
+                    KeyCode::Char('p') => {
+                        if let Some((s, i)) = self.table_state.selected().and_then(|row| self.table_point(row)) {
+                            self.data_series[s].toggle_pin(i);
+                        }
+                    }
+                    KeyCode::Char('R') => self.reload_csv(),
+                    KeyCode::Char('P') => self.reload_theme(),
+                    KeyCode::Char('T') => self.template_idx = Some(0),
+                    KeyCode::Char('L') => self.toggle_lock(),
+                    KeyCode::Char('n') => self.command = Some("new ".to_string()),
+                    KeyCode::Char('r') => self.start_rename(),
+                    KeyCode::Char('i') => self.point_stats = self.table_state.selected().is_some(),
+                    KeyCode::Char('x') => {
+                        self.trash_idx = 0;
+                        self.mode = ViewMode::Trash;
+                    }
+                    KeyCode::Char('[') => self.cycle_serie(-1),
+                    KeyCode::Char(']') => self.cycle_serie(1),
+                    KeyCode::Char('a') => {
+                        self.table_all = !self.table_all;
+                        self.table_state.select(None);
+                    }
+                    KeyCode::Char('o') => {
+                        self.table_order = match self.table_order {
+                            SortOrder::NewestFirst => SortOrder::OldestFirst,
+                            SortOrder::OldestFirst => SortOrder::NewestFirst,
+                        };
+                        self.table_state.select(None);
+                    }
+                    KeyCode::Esc => self.mode = ViewMode::Menu,
+                    _ => {}
+                }
+            },
+            true => {
+                match key {
+                    KeyCode::Esc => self.confirm_delete = false,
+                    KeyCode::Left => self.confirm_idx = self.confirm_idx.saturating_sub(1),
+                    KeyCode::Right => self.confirm_idx = (self.confirm_idx + 1).min(DELETE_CHOICES.len() - 1),
+                    KeyCode::Tab => self.cycle_confirm_idx(),
+                    KeyCode::Enter => {
+                        if let Some(scope) = DELETE_CHOICES[self.confirm_idx].1 {
+                            self.delete(scope);
+                        }
+                        self.confirm_delete = false;
+                        self.confirm_idx = 0;
+                    },
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn handle_help_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Char('q') => self.exit = true,
+            KeyCode::Char('g') => self.mode = ViewMode::Graph,
+            KeyCode::Char('m') => self.mode = ViewMode::Menu,
+            KeyCode::Char('t') => self.mode = ViewMode::Table,
+            KeyCode::Char('f') => self.mode = ViewMode::Spectrum,
+            KeyCode::Char('c') => self.mode = ViewMode::Compare,
+            KeyCode::Char('s') => self.mode = ViewMode::Settings,
+            KeyCode::Char('S') => self.mode = ViewMode::Series,
+            KeyCode::Up | KeyCode::Char('k') => self.help_scroll = self.help_scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.help_scroll += 1,
+            KeyCode::Esc => self.mode = ViewMode::Menu,
+            _ => {}
+        }
+    }
+
+    // The key reference as Markdown, one table per section
+    fn write_keys(&mut self, path: &str) {
+        let mut out = String::from("# tracktui keys\n");
+        for (section, keys) in KEYMAP {
+            out.push_str(&format!("\n## {}\n\n| Key | Action |\n| --- | --- |\n", section));
+            for &(key, action) in keys {
+                let key = match section {
+                    "Commands" => format!(":{}", key),
+                    _ => key.to_string(),
+                };
+                // Pipes would end the cell
+                out.push_str(&format!("| `{}` | {} |\n", key.replace('|', "\\|"), action));
+            }
+        }
+        self.status_msg = match fs::write(path, out) {
+            Ok(()) => format!("Wrote {}", path),
+            Err(e) => format!("Could not write {}: {}", path, e),
+        };
+    }
+
+    fn handle_spectrum_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Char('q') => self.exit = true,
+            KeyCode::Char('g') => self.mode = ViewMode::Graph,
+            KeyCode::Char('m') => self.mode = ViewMode::Menu,
+            KeyCode::Char('t') => self.mode = ViewMode::Table,
+            KeyCode::Char('c') => self.mode = ViewMode::Compare,
+            KeyCode::Char('h') => self.mode = ViewMode::Help,
+            KeyCode::Char('s') => self.mode = ViewMode::Settings,
+            KeyCode::Char('S') => self.mode = ViewMode::Series,
+            KeyCode::Esc => self.mode = ViewMode::Menu,
+            _ => {}
+        }
+    }
+
+    fn open_batch(&mut self) {
+        self.batch = vec![vec![String::new(); self.input_fields().len()]];
+        self.batch_cell = (0, 0);
+        self.batch_invalid.clear();
+        self.status_msg = "Type one point per row".to_string();
+        self.mode = ViewMode::Batch;
+    }
+
+    // Moves to the next cell, or the start of the next row, adding a row after the last one
+    fn batch_next(&mut self, next_row: bool) {
+        let n_columns = self.input_fields().len();
+        let (mut row, mut col) = self.batch_cell;
+        match next_row {
+            true => (row, col) = (row + 1, 0),
+            false => {
+                col += 1;
+                if col == n_columns {
+                    (row, col) = (row + 1, 0);
+                }
+            }
+        }
+        if row == self.batch.len() {
+            self.batch.push(vec![String::new(); n_columns]);
+        }
+        self.batch_cell = (row, col);
+    }
+
+    fn handle_batch_input(&mut self, key: KeyCode) {
+        let n_columns = self.input_fields().len();
+        let (row, col) = self.batch_cell;
+        match key {
+            // X may be a date with a time of day
+            KeyCode::Char(c) if c.is_ascii_digit() || c == '.' || c == '-' || (col == 0 && (c == ':' || c == ' ')) => {
+                let text = &mut self.batch[row][col];
+                if text.len() < if col == 0 { 16 } else { 12 } {
+                    text.push(c);
+                }
+            }
+            KeyCode::Backspace => {
+                self.batch[row][col].pop();
+            }
+            KeyCode::Tab => self.batch_next(false),
+            KeyCode::Enter => self.batch_next(true),
+            KeyCode::BackTab | KeyCode::Left => self.batch_cell.1 = col.saturating_sub(1),
+            KeyCode::Right => self.batch_cell.1 = (col + 1).min(n_columns - 1),
+            KeyCode::Up => self.batch_cell.0 = row.saturating_sub(1),
+            KeyCode::Down => self.batch_cell.0 = (row + 1).min(self.batch.len() - 1),
+            KeyCode::Char('d') => {
+                match self.batch.len() {
+                    1 => self.batch[0] = vec![String::new(); n_columns],
+                    _ => {
+                        self.batch.remove(row);
+                        self.batch_cell.0 = row.min(self.batch.len() - 1);
+                    }
+                }
+                self.batch_invalid.clear();
+            }
+            KeyCode::Char('w') => self.commit_batch(),
+            KeyCode::Esc => {
+                self.batch.clear();
+                self.mode = ViewMode::Graph;
+                self.status_msg = "Batch entry discarded".to_string();
+            }
+            _ => {}
+        }
+    }
+
+    // Inserts every filled row, or none of them if any row is invalid
+    fn commit_batch(&mut self) {
+        let fields = self.input_fields();
+        let mut points = Vec::new();
+        self.batch_invalid.clear();
+
+        for (i, row) in self.batch.iter().enumerate() {
+            if row.iter().all(String::is_empty) {
+                continue;
+            }
+            match parse_point(&fields, row) {
+                Some(point) => points.push(point),
+                None => self.batch_invalid.push(i),
+            }
+        }
+
+        if !self.batch_invalid.is_empty() {
+            let rows: Vec<String> = self.batch_invalid.iter().map(|i| (i + 1).to_string()).collect();
+            self.status_msg = format!("Nothing inserted, fix rows {}", rows.join(", "));
+            return;
+        }
+
+        self.insert_checked(points);
+    }
+
+    // Inserts right away while the series' rules hold, otherwise asks first
+    fn insert_checked(&mut self, points: Vec<(f64, f64, PointMeta)>) {
+        if self.data_series[self.selected_serie].aggregate.is_some() {
+            self.status_msg = "This series is computed from its group".to_string();
+            return;
+        }
         let serie = &self.data_series[self.selected_serie];
-        let dataset = Dataset::default()
-            .name("")
-            .marker(symbols::Marker::Braille)
-            .graph_type(GraphType::Line)
-            .style(Style::default().fg(Color::Cyan))
-            .data(&serie.data);
+        // Snapped before the checks, so they judge the values that get stored
+        let points: Vec<_> = points.into_iter()
+            .map(|(x, y, mut meta)| {
+                meta.values = meta.values.iter().map(|&v| serie.snap(v)).collect();
+                (x, serie.snap(y), meta)
+            })
+            .collect();
+        if let Some(rejection) = serie.rejections(&points).first() {
+            self.status_msg = format!("Refused, {} (rule increasing strict)", rejection);
+            return;
+        }
+        let mut violations = serie.violations(&points);
+        if self.config.trend_check {
+            violations.extend(serie.off_trend(&points));
+        }
+        match violations.is_empty() {
+            true => self.insert_points(points),
+            false => self.pending_insert = Some(PendingInsert { points, violations }),
+        }
+    }
+
+    // Inserts points from the insert bar or the batch grid and closes it
+    fn insert_points(&mut self, points: Vec<(f64, f64, PointMeta)>) {
+        self.status_msg = match points.as_slice() {
+            [(x, y, meta)] => {
+                let mut point = format!("{:.2}", y);
+                for v in &meta.values {
+                    point.push_str(&format!(" / {:.2}", v));
+                }
+                if let Some(e) = meta.error {
+                    point.push_str(&format!(" ± {:.2}", e));
+                }
+                format!("Inserted point ({:.2}, {})", x, point)
+            }
+            _ => format!("Inserted {} points", points.len()),
+        };
 
-        let (x_max, y_max) = serie.get_bounds();
-        let (x_labels, y_labels) = serie.get_labels();
+        for (x, y, meta) in points {
+            self.data_series[self.selected_serie].insert_point(x, y, meta);
+            self.point_added(self.selected_serie, x, y);
+        }
 
-        let chart = Chart::new(vec![dataset])
-            .block(Block::bordered()
-                .title(format!(" {} ", serie.name))
-                .title_alignment(Alignment::Center))
-            .x_axis(Axis::default()
-                .title("X")
-                .bounds([0.0, x_max])
-                .labels(x_labels))
-            .y_axis(Axis::default()
-                .title("Y")
-                .bounds([0.0, y_max])
-                .labels(y_labels));
+        match self.mode {
+            ViewMode::Batch => {
+                self.batch.clear();
+                self.mode = ViewMode::Graph;
+            }
+            _ => {
+                self.input_mode = InputMode::Normal;
+                self.clear_inputs();
+            }
+        }
+    }
 
-        frame.render_widget(chart, area);
+    fn set_rule(&mut self, arg: &str) {
+        let rules = &mut self.data_series[self.selected_serie].rules;
+        let parts: Vec<&str> = arg.split_whitespace().collect();
+        match parts.as_slice() {
+            ["positive"] => rules.positive = !rules.positive,
+            ["increasing"] => {
+                rules.increasing_x = !rules.increasing_x;
+                rules.strict_x = false;
+            }
+            ["increasing", "strict"] => {
+                rules.strict_x = !rules.strict_x;
+                rules.increasing_x = rules.strict_x;
+            }
+            ["min", "off"] => rules.y_min = None,
+            ["max", "off"] => rules.y_max = None,
+            ["min", v] | ["max", v] => {
+                let Ok(v) = v.parse::<f64>() else {
+                    self.status_msg = format!("Not a number: {}", v);
+                    return;
+                };
+                match parts[0] {
+                    "min" => rules.y_min = Some(v),
+                    _ => rules.y_max = Some(v),
+                }
+            }
+            ["clear"] => *rules = Rules::default(),
+            _ => {
+                self.status_msg = RULE_USAGE.to_string();
+                return;
+            }
+        }
+        self.status_msg = format!("Rules: {}", rules.describe());
     }
 
-    fn handle_events(&mut self) -> Result<()> {
-        if let Event::Key(key) = event::read()? {
-            if key.kind == KeyEventKind::Press {
-                match self.mode {
-                    ViewMode::Graph => self.handle_graph_input(key.code),
-                    ViewMode::Table => self.handle_table_input(key.code),
-                    ViewMode::Menu => self.handle_menu_input(key.code),
-                    ViewMode::Help => self.handle_help_input(key.code),
-                }
+    fn handle_compare_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Char('q') => self.exit = true,
+            KeyCode::Char('g') => self.mode = ViewMode::Graph,
+            KeyCode::Char('m') => self.mode = ViewMode::Menu,
+            KeyCode::Char('t') => self.mode = ViewMode::Table,
+            KeyCode::Char('f') => self.mode = ViewMode::Spectrum,
+            KeyCode::Char('h') => self.mode = ViewMode::Help,
+            KeyCode::Char('s') => self.mode = ViewMode::Settings,
+            KeyCode::Char('S') => self.mode = ViewMode::Series,
+            KeyCode::Char('p') => {
+                self.compare_period = match self.compare_period {
+                    Period::Week => Period::Month,
+                    Period::Month => Period::Week,
+                };
             }
+            KeyCode::Char('d') => self.compare_histogram = !self.compare_histogram,
+            KeyCode::Esc => self.mode = ViewMode::Menu,
+            _ => {}
         }
-        Ok(())
     }
 
-    fn select_previous(&mut self) {
-        let i = match self.table_state.selected() {
-            Some(i) => {
-                if i >= self.data_series[self.selected_serie].data.len() - 1 {
-                    0
-                } else {
-                    i + 1
+    fn handle_settings_input(&mut self, key: KeyCode) {
+        if let Some(text) = &mut self.settings_edit {
+            match key {
+                KeyCode::Char(c) => text.push(c),
+                KeyCode::Backspace => {
+                    text.pop();
+                }
+                KeyCode::Enter => {
+                    let text = self.settings_edit.take().unwrap_or_default();
+                    self.apply_setting_text(&text);
                 }
+                KeyCode::Esc => self.settings_edit = None,
+                _ => {}
             }
-            None => 0,
-        };
-        self.table_state.select(Some(i));
+            return;
+        }
+
+        let setting = SETTINGS[self.settings_idx];
+        match key {
+            KeyCode::Char('q') => self.exit = true,
+            KeyCode::Char('g') => self.mode = ViewMode::Graph,
+            KeyCode::Char('m') => self.mode = ViewMode::Menu,
+            KeyCode::Char('t') => self.mode = ViewMode::Table,
+            KeyCode::Char('f') => self.mode = ViewMode::Spectrum,
+            KeyCode::Char('c') => self.mode = ViewMode::Compare,
+            KeyCode::Char('h') => self.mode = ViewMode::Help,
+            KeyCode::Char('S') => self.mode = ViewMode::Series,
+            KeyCode::Up | KeyCode::Char('k') => self.settings_idx = self.settings_idx.checked_sub(1).unwrap_or(SETTINGS.len() - 1),
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => self.settings_idx = (self.settings_idx + 1) % SETTINGS.len(),
+            KeyCode::Left => self.adjust_setting(setting, -1),
+            KeyCode::Right | KeyCode::Enter => self.adjust_setting(setting, 1),
+            KeyCode::Esc => self.mode = ViewMode::Menu,
+            _ => {}
+        }
     }
 
-    fn select_next(&mut self) {
-        let i = match self.table_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.data_series[self.selected_serie].data.len() - 1
-                } else {
-                    i - 1
+    // Steps a setting through its values, text settings open for editing instead.
+    // App settings are written to the config file right away, series ones with the data
+    fn adjust_setting(&mut self, setting: Setting, step: isize) {
+        if matches!(setting, Setting::Unit | Setting::Decimals | Setting::InputStep | Setting::Description | Setting::UnitScale | Setting::Goal | Setting::Axis | Setting::Labels | Setting::SeriesMarker | Setting::Prefill)
+            && !self.writable() {
+            return;
+        }
+
+        let serie = &mut self.data_series[self.selected_serie];
+        match setting {
+            Setting::Unit => {
+                self.settings_edit = Some(serie.unit.clone());
+                return;
+            }
+            Setting::Description => {
+                self.settings_edit = Some(serie.description.clone());
+                return;
+            }
+            Setting::Goal => {
+                self.settings_edit = Some(serie.goal.map_or(String::new(), |g| g.to_string()));
+                return;
+            }
+            Setting::InputStep => {
+                self.settings_edit = Some(serie.input_step.map_or(String::new(), |s| s.to_string()));
+                return;
+            }
+            Setting::UnitScale => {
+                serie.fixed_unit = !serie.fixed_unit;
+                return;
+            }
+            // The app's precision comes before 0
+            Setting::Decimals => {
+                serie.precision = match (serie.precision, step > 0) {
+                    (None, true) => Some(0),
+                    (None, false) => Some(10),
+                    (Some(0), false) => None,
+                    (Some(10), true) => None,
+                    (Some(p), true) => Some(p + 1),
+                    (Some(p), false) => Some(p - 1),
+                };
+                return;
+            }
+            Setting::Prefill => {
+                serie.prefill = cycle(&Prefill::ALL, serie.prefill, step);
+                return;
+            }
+            // The app's marker comes before the first style
+            Setting::SeriesMarker => {
+                let options = [None, Some(MarkerStyle::Braille), Some(MarkerStyle::Dot), Some(MarkerStyle::Block), Some(MarkerStyle::HalfBlock)];
+                serie.marker = cycle(&options, serie.marker, step);
+                return;
+            }
+            Setting::Axis => {
+                serie.y_axis = cycle(&[YAxis::Zero, YAxis::Fit, YAxis::Log], serie.y_axis, step);
+                return;
+            }
+            Setting::Labels => {
+                serie.label_format = cycle(&LabelFormat::ALL, serie.label_format, step);
+                return;
+            }
+            Setting::RefreshRate => {
+                self.config.refresh_rate = (self.config.refresh_rate as isize + 5 * step).clamp(5, 120) as u32;
+            }
+            Setting::TableOrder => {
+                self.config.table_order = cycle(&[SortOrder::NewestFirst, SortOrder::OldestFirst], self.config.table_order, step);
+                self.table_order = self.config.table_order;
+            }
+            Setting::Precision => {
+                self.config.precision = (self.config.precision as isize + step).clamp(0, 10) as usize;
+            }
+            Setting::NudgeStep => {
+                let steps = [0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 5.0, 10.0];
+                self.config.nudge_step = cycle(&steps, self.config.nudge_step, step);
+            }
+            Setting::TrendCheck => self.config.trend_check = !self.config.trend_check,
+            Setting::Autosave => {
+                self.config.autosave = (self.config.autosave as isize + step).clamp(0, 60) as u32;
+                self.last_save = Some(Instant::now());
+            }
+            Setting::DefaultView => {
+                let views = [ViewMode::Graph, ViewMode::Table, ViewMode::Spectrum, ViewMode::Menu, ViewMode::Series];
+                self.config.default_view = cycle(&views, self.config.default_view, step);
+            }
+            Setting::Marker => {
+                self.config.marker = cycle(&MarkerStyle::ALL, self.config.marker, step);
+            }
+            // Built-in colors come before the files in themes/
+            Setting::Theme => {
+                let mut names = vec![String::new()];
+                names.extend(Theme::list());
+                let i = names.iter().position(|n| *n == self.config.theme).unwrap_or(0) as isize + step;
+                self.config.theme = names[i.rem_euclid(names.len() as isize) as usize].clone();
+                if !self.load_theme() {
+                    return;
                 }
             }
-            None => 0,
+            Setting::Animations => self.config.animations = !self.config.animations,
+            Setting::Mouse => {
+                self.config.mouse = !self.config.mouse;
+                set_mouse_capture(self.config.mouse);
+            }
+            // Adds or removes the selected series
+            Setting::Kpis => match self.config.kpis.iter().position(|k| *k == serie.name) {
+                Some(i) => {
+                    self.config.kpis.remove(i);
+                }
+                None if self.config.kpis.len() >= MAX_KPIS => {
+                    self.status_msg = format!("At most {} KPIs, remove one first", MAX_KPIS);
+                    return;
+                }
+                None => self.config.kpis.push(serie.name.clone()),
+            },
+        }
+
+        self.status_msg = match self.save_config() {
+            Ok(()) => format!("Saved {}", CONFIG_PATH),
+            Err(e) => format!("Could not write {}: {}", CONFIG_PATH, e),
         };
-        self.table_state.select(Some(i));
     }
 
-    fn cycle_confirm_idx(&mut self) {
-        self.confirm_idx = match self.confirm_idx {
-            1 => 0,
-            0 => 1,
-            _ => 0
+    fn apply_setting_text(&mut self, text: &str) {
+        let serie = &mut self.data_series[self.selected_serie];
+        match (SETTINGS[self.settings_idx], text.trim()) {
+            (Setting::Unit, text) => serie.unit = text.to_string(),
+            (Setting::Description, text) => serie.description = text.to_string(),
+            (Setting::Goal, "") => serie.goal = None,
+            (Setting::Goal, text) => match text.parse::<f64>() {
+                Ok(goal) => serie.goal = Some(goal),
+                Err(_) => self.status_msg = format!("Not a number: {}", text),
+            },
+            (Setting::InputStep, "") => serie.input_step = None,
+            (Setting::InputStep, text) => match text.parse::<f64>() {
+                Ok(step) if step > 0.0 && step.is_finite() => serie.input_step = Some(step),
+                _ => self.status_msg = format!("Step must be a number above 0: {}", text),
+            },
+            _ => {}
         }
     }
-    
-    fn handle_table_input(&mut self, key: KeyCode) {
-        match self.confirm_delete {
-            false => {
-                match key {
-                    KeyCode::Char('q') => self.exit = true,
-                    KeyCode::Char('g') => self.mode = ViewMode::Graph,
-                    KeyCode::Char('m') => self.mode = ViewMode::Menu,
-                    KeyCode::Char('h') => self.mode = ViewMode::Help,
-                    KeyCode::Up | KeyCode::Char('k') => self.select_next(),
-                    KeyCode::Down | KeyCode::Char('j') => self.select_previous(), 
-                    KeyCode::Char('d') => self.confirm_delete = true,
-                    KeyCode::Esc => self.mode = ViewMode::Menu,
-                    _ => {}
-                }
-            },
-            true => {
-                match key {
-                    KeyCode::Esc => self.confirm_delete = false,
-                    KeyCode::Left => self.confirm_idx = 0,
-                    KeyCode::Right => self.confirm_idx = 1,
-                    KeyCode::Tab => self.cycle_confirm_idx(),
-                    KeyCode::Enter => {
-                        if self.confirm_idx == 0 {
-                            if let Some(i) = self.table_state.selected() {
-                                self.data_series[self.selected_serie].data.remove(i);
-                                self.confirm_delete = false;
-                            }
-                        } else {
-                            self.confirm_delete = false;
-                        }
-                    },
-                    _ => {}
-                }
+
+    fn handle_menu_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Char('q') => self.exit = true,
+            KeyCode::Char('g') => self.mode = ViewMode::Graph,
+            KeyCode::Char('t') => self.mode = ViewMode::Table,
+            KeyCode::Char('f') => self.mode = ViewMode::Spectrum,
+            KeyCode::Char('c') => self.mode = ViewMode::Compare,
+            KeyCode::Char('h') => self.mode = ViewMode::Help,
+            KeyCode::Char('s') => self.mode = ViewMode::Settings,
+            KeyCode::Char('S') => self.mode = ViewMode::Series,
+            KeyCode::Char('x') => {
+                self.trash_idx = 0;
+                self.mode = ViewMode::Trash;
             }
+            KeyCode::Char('R') => self.reload_csv(),
+            KeyCode::Char('P') => self.reload_theme(),
+            KeyCode::Char('L') => self.toggle_lock(),
+            _ => {}
         }
     }
 
-    fn handle_help_input(&mut self, key: KeyCode) {
+    fn handle_trash_input(&mut self, key: KeyCode) {
+        let len = self.data_series[self.selected_serie].trash.len();
+        if self.confirm_empty {
+            match key {
+                KeyCode::Enter | KeyCode::Char('y') => {
+                    self.data_series[self.selected_serie].trash.clear();
+                    self.trash_idx = 0;
+                    self.status_msg = format!("Deleted {} points for good", len);
+                    self.confirm_empty = false;
+                }
+                KeyCode::Esc | KeyCode::Char('n') => self.confirm_empty = false,
+                _ => {}
+            }
+            return;
+        }
+
+        if len > 0 && matches!(key, KeyCode::Enter | KeyCode::Char('r' | 'E')) && !self.writable() {
+            return;
+        }
         match key {
             KeyCode::Char('q') => self.exit = true,
             KeyCode::Char('g') => self.mode = ViewMode::Graph,
             KeyCode::Char('m') => self.mode = ViewMode::Menu,
             KeyCode::Char('t') => self.mode = ViewMode::Table,
-            KeyCode::Esc => self.mode = ViewMode::Menu,
+            KeyCode::Char('h') => self.mode = ViewMode::Help,
+            KeyCode::Char('S') => self.mode = ViewMode::Series,
+            KeyCode::Up | KeyCode::Char('k') => self.trash_idx = self.trash_idx.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.trash_idx = (self.trash_idx + 1).min(len.saturating_sub(1)),
+            KeyCode::Enter | KeyCode::Char('r') if len > 0 => {
+                // Listed the latest first
+                let serie = &mut self.data_series[self.selected_serie];
+                if serie.aggregate.is_some() {
+                    self.status_msg = "This series is computed from its group".to_string();
+                    return;
+                }
+                serie.restore_point(len - 1 - self.trash_idx.min(len - 1));
+                self.trash_idx = self.trash_idx.min(len.saturating_sub(2));
+                self.status_msg = "Restored the point".to_string();
+            }
+            KeyCode::Char('E') if len > 0 => self.confirm_empty = true,
+            KeyCode::Esc => self.mode = ViewMode::Table,
             _ => {}
         }
     }
 
-    fn handle_menu_input(&mut self, key: KeyCode) {
+    fn handle_series_input(&mut self, key: KeyCode) {
+        if let Some(query) = &mut self.series_search {
+            match key {
+                KeyCode::Char(c) => {
+                    query.push(c);
+                    self.search_idx = 0;
+                }
+                KeyCode::Backspace => {
+                    query.pop();
+                    self.search_idx = 0;
+                }
+                KeyCode::Up => self.search_idx = self.search_idx.saturating_sub(1),
+                KeyCode::Down => self.search_idx = (self.search_idx + 1).min(self.search_results().len().saturating_sub(1)),
+                KeyCode::Enter => {
+                    if let Some(&s) = self.search_results().get(self.search_idx) {
+                        self.series_search = None;
+                        self.select_serie(s);
+                        self.mode = ViewMode::Graph;
+                    }
+                }
+                KeyCode::Esc => self.series_search = None,
+                _ => {}
+            }
+            return;
+        }
+
+        if self.confirm_delete {
+            match key {
+                KeyCode::Enter | KeyCode::Char('y') => {
+                    self.delete_series(self.selected_serie);
+                    self.series_idx = self.series_idx.min(self.series_list().len().saturating_sub(1));
+                    self.confirm_delete = false;
+                }
+                KeyCode::Esc | KeyCode::Char('n') => self.confirm_delete = false,
+                _ => {}
+            }
+            return;
+        }
+
+        if self.confirm_clear {
+            match key {
+                KeyCode::Enter | KeyCode::Char('y') => {
+                    let s = self.selected_serie;
+                    let n = self.data_series[s].clear_points();
+                    // Undo refers to points by x and y
+                    self.undo.retain(|&(u, ..)| u != s);
+                    self.status_msg = format!("Cleared {} points of {}, they are in the trash", n, self.data_series[s].name);
+                    self.confirm_clear = false;
+                }
+                KeyCode::Esc | KeyCode::Char('n') => self.confirm_clear = false,
+                _ => {}
+            }
+            return;
+        }
+
+        if matches!(key, KeyCode::Char('r' | 'd' | 'X' | 'C' | 'K' | 'J' | '#' | 'a' | 'p' | 'D')) && !self.writable() {
+            return;
+        }
         match key {
             KeyCode::Char('q') => self.exit = true,
             KeyCode::Char('g') => self.mode = ViewMode::Graph,
+            KeyCode::Char('m') => self.mode = ViewMode::Menu,
             KeyCode::Char('t') => self.mode = ViewMode::Table,
+            KeyCode::Char('f') => self.mode = ViewMode::Spectrum,
+            KeyCode::Char('c') => self.mode = ViewMode::Compare,
             KeyCode::Char('h') => self.mode = ViewMode::Help,
+            KeyCode::Char('s') => self.mode = ViewMode::Settings,
+            KeyCode::Up | KeyCode::Char('k') => self.series_idx = self.series_idx.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.series_idx = (self.series_idx + 1).min(self.series_list().len().saturating_sub(1));
+            }
+            KeyCode::Enter => match self.series_list().get(self.series_idx) {
+                Some(ListEntry::Group(group)) => match self.collapsed.iter().position(|c| c == group) {
+                    Some(i) => {
+                        self.collapsed.remove(i);
+                    }
+                    None => self.collapsed.push(group.clone()),
+                },
+                Some(&ListEntry::Serie(s)) => {
+                    self.select_serie(s);
+                    self.mode = ViewMode::Graph;
+                }
+                None => {}
+            },
+            KeyCode::Char('r') => {
+                if let Some(&ListEntry::Serie(s)) = self.series_list().get(self.series_idx) {
+                    self.select_serie(s);
+                    self.start_rename();
+                }
+            }
+            KeyCode::Char('d') => {
+                if let Some(&ListEntry::Serie(s)) = self.series_list().get(self.series_idx) {
+                    self.select_serie(s);
+                    self.confirm_delete = true;
+                }
+            }
+            KeyCode::Char('X') => {
+                if let Some(&ListEntry::Serie(s)) = self.series_list().get(self.series_idx) {
+                    self.select_serie(s);
+                    match (self.data_series[s].aggregate.is_some(), self.data_series[s].data.is_empty()) {
+                        (true, _) => self.status_msg = "This series is computed from its group".to_string(),
+                        (_, true) => self.status_msg = format!("{} has no points", self.data_series[s].name),
+                        _ => self.confirm_clear = true,
+                    }
+                }
+            }
+            KeyCode::Char('C') => {
+                if let Some(&ListEntry::Serie(s)) = self.series_list().get(self.series_idx) {
+                    self.cycle_color(s);
+                }
+            }
+            KeyCode::Char('K') => {
+                if let Some(&ListEntry::Serie(s)) = self.series_list().get(self.series_idx) {
+                    self.move_serie(s, true);
+                }
+            }
+            KeyCode::Char('J') => {
+                if let Some(&ListEntry::Serie(s)) = self.series_list().get(self.series_idx) {
+                    self.move_serie(s, false);
+                }
+            }
+            KeyCode::Char('#') => {
+                if let Some(&ListEntry::Serie(s)) = self.series_list().get(self.series_idx) {
+                    self.select_serie(s);
+                    self.command = Some(format!("tags {}", self.data_series[s].tags.join(", ")));
+                }
+            }
+            KeyCode::Char('F') => self.cycle_tag_filter(),
+            KeyCode::Char('a') => {
+                if let Some(&ListEntry::Serie(s)) = self.series_list().get(self.series_idx) {
+                    let serie = &mut self.data_series[s];
+                    serie.archived = !serie.archived;
+                    self.status_msg = match serie.archived {
+                        true => format!("Archived {} (A: show archived)", serie.name),
+                        false => format!("Unarchived {}", serie.name),
+                    };
+                    self.series_idx = self.series_idx.min(self.series_list().len().saturating_sub(1));
+                }
+            }
+            KeyCode::Char('p') => {
+                if let Some(&ListEntry::Serie(s)) = self.series_list().get(self.series_idx) {
+                    let serie = &mut self.data_series[s];
+                    serie.favorite = !serie.favorite;
+                    let name = serie.name.clone();
+                    // Follow the series to or from the top of the list
+                    if let Some(i) = self.series_list().iter().position(|e| *e == ListEntry::Serie(s)) {
+                        self.series_idx = i;
+                    }
+                    self.status_msg = match self.favorites().iter().position(|&f| f == s) {
+                        Some(n) if n < 9 => format!("Pinned {} to key {}", name, n + 1),
+                        Some(_) => format!("Pinned {}, only the first 9 have a key", name),
+                        None => format!("Unpinned {}", name),
+                    };
+                }
+            }
+            KeyCode::Char('A') => {
+                self.show_archived = !self.show_archived;
+                self.series_idx = self.series_idx.min(self.series_list().len().saturating_sub(1));
+                self.status_msg = match self.show_archived {
+                    true => "Showing archived series".to_string(),
+                    false => "Hiding archived series".to_string(),
+                };
+            }
+            KeyCode::Char('/') => {
+                self.series_search = Some(String::new());
+                self.search_idx = 0;
+            }
+            KeyCode::Char('D') => {
+                if let Some(&ListEntry::Serie(s)) = self.series_list().get(self.series_idx) {
+                    self.select_serie(s);
+                    self.duplicate_serie();
+                }
+            }
+            KeyCode::Char('o') => {
+                if let Some(&ListEntry::Serie(s)) = self.series_list().get(self.series_idx) {
+                    self.toggle_overlay(s);
+                }
+            }
+            KeyCode::Char('O') => {
+                self.overlay.clear();
+                self.status_msg = "Cleared the overlay".to_string();
+            }
+            KeyCode::Esc => self.mode = ViewMode::Menu,
             _ => {}
         }
     }
 
-    fn cycle_field(&mut self) {
-        self.input_field = match self.input_field {
-            InputField::X => { InputField::Y }
-            InputField::Y => { InputField::X }
+    // False, with a hint in the status bar, while the data is locked
+    fn writable(&mut self) -> bool {
+        if self.read_only {
+            self.status_msg = "Read-only (L: unlock)".to_string();
+        }
+        !self.read_only
+    }
+
+    fn toggle_lock(&mut self) {
+        if self.loading.is_some() {
+            self.status_msg = "Still loading data.csv".to_string();
+            return;
+        }
+        self.read_only = !self.read_only;
+        self.status_msg = match self.read_only {
+            true => "Locked, changes are not saved (L: unlock)".to_string(),
+            false => "Unlocked".to_string(),
+        };
+    }
+
+    fn input_fields(&self) -> Vec<InputField> {
+        let n_values = self.data_series[self.selected_serie].extra_columns().len();
+        let mut fields = vec![InputField::X, InputField::Y];
+        fields.extend((0..n_values).map(InputField::Value));
+        fields.push(InputField::Error);
+        fields
+    }
+
+    fn input_buffer(&mut self) -> &mut String {
+        match self.input_field {
+            InputField::X => &mut self.input_x,
+            InputField::Y => &mut self.input_y,
+            InputField::Value(j) => &mut self.input_values[j],
+            InputField::Error => &mut self.input_error,
+        }
+    }
+
+    fn clear_inputs(&mut self) {
+        let n_values = self.data_series[self.selected_serie].extra_columns().len();
+        self.input_x.clear();
+        self.input_y.clear();
+        self.input_values = vec![String::new(); n_values];
+        self.input_error.clear();
+    }
+
+    fn prefill_inputs(&mut self) {
+        let serie = &self.data_series[self.selected_serie];
+        if serie.prefill.x {
+            self.input_x = serie.next_x().unwrap_or_default();
+        }
+        if serie.prefill.y && let Some(&(_, y)) = serie.data.last() {
+            let last = serie.data.len() - 1;
+            let precision = serie.precision(self.config.precision);
+            self.input_y = format_value(y, precision);
+            for (input, &v) in self.input_values.iter_mut().zip(serie.values(last)) {
+                *input = format_value(v, precision);
+            }
+        }
+    }
+
+    // Moves `step` fields forward, wrapping around or stopping at the ends
+    fn move_field(&mut self, step: isize, wrap: bool) {
+        let fields = self.input_fields();
+        let i = fields.iter().position(|f| *f == self.input_field).unwrap_or(0) as isize + step;
+        let i = match wrap {
+            true => i.rem_euclid(fields.len() as isize),
+            false => i.clamp(0, fields.len() as isize - 1),
         };
+        self.input_field = fields[i as usize];
+    }
+
+    fn cycle_field(&mut self) {
+        self.move_field(1, true);
     }
 
     fn handle_graph_input(&mut self, key: KeyCode) {
         match self.input_mode {
 
             InputMode::Normal => {
+                if matches!(key, KeyCode::Char('T' | 'b' | 'n' | 'C' | 'p' | 'a' | 'o' | 'r' | 'M' | 'i')) && !self.writable() {
+                    return;
+                }
                 match key {
                     KeyCode::Char('q') => self.exit = true,
                     KeyCode::Char('h') => self.mode = ViewMode::Help,
                     KeyCode::Char('m') => self.mode = ViewMode::Menu,
                     KeyCode::Char('t') => self.mode = ViewMode::Table,
+                    KeyCode::Char('f') => self.mode = ViewMode::Spectrum,
+                    KeyCode::Char('c') => self.mode = ViewMode::Compare,
+                    KeyCode::Char('s') => self.mode = ViewMode::Settings,
+                    KeyCode::Char('S') => self.mode = ViewMode::Series,
+                    KeyCode::Char('R') => self.reload_csv(),
+                    KeyCode::Char('P') => self.reload_theme(),
+                    KeyCode::Char('T') => self.template_idx = Some(0),
+                    KeyCode::Char('L') => self.toggle_lock(),
+                    KeyCode::Char('=') | KeyCode::Char('0') => {
+                        self.x_window = None;
+                        self.y_window = None;
+                    }
+                    KeyCode::Char('+') => self.zoom_graph(2.0 / 3.0),
+                    KeyCode::Char('-') => self.zoom_graph(1.5),
+                    KeyCode::Left => self.pan_graph(-0.25, 0.0),
+                    KeyCode::Right => self.pan_graph(0.25, 0.0),
+                    KeyCode::Up | KeyCode::Char('k') => self.pan_graph(0.0, 0.25),
+                    KeyCode::Down | KeyCode::Char('j') => self.pan_graph(0.0, -0.25),
+                    KeyCode::Char('1') => self.window_preset(7.0),
+                    KeyCode::Char('2') => self.window_preset(30.0),
+                    KeyCode::Char('3') => self.window_preset(90.0),
+                    KeyCode::Char('4') => self.window_preset(365.0),
+                    KeyCode::Char('b') => self.open_batch(),
+                    KeyCode::Char('n') => self.command = Some("new ".to_string()),
+                    KeyCode::Char('[') => self.cycle_serie(-1),
+                    KeyCode::Char(']') => self.cycle_serie(1),
+                    KeyCode::Char('C') => self.cycle_color(self.selected_serie),
+                    KeyCode::Char('p') => {
+                        let serie = &mut self.data_series[self.selected_serie];
+                        serie.scatter = !serie.scatter;
+                        self.status_msg = match serie.scatter {
+                            true => format!("{} is drawn as points", serie.name),
+                            false => format!("{} is drawn as lines", serie.name),
+                        };
+                    }
+                    KeyCode::Char('a') => self.toggle_average(),
+                    KeyCode::Char('o') => self.cycle_overlays(),
+                    KeyCode::Char('r') => self.toggle_trend(),
+                    KeyCode::Char('M') => {
+                        let serie = &mut self.data_series[self.selected_serie];
+                        serie.reference_lines = !serie.reference_lines;
+                        self.status_msg = match serie.reference_lines {
+                            true => "Mean, min and max lines, l hides one".to_string(),
+                            false => "Mean, min and max lines hidden".to_string(),
+                        };
+                    }
+                    KeyCode::Char('l') => {
+                        self.input_mode = InputMode::Legend;
+                        self.legend_idx = 0;
+                        self.status_msg = "⇅: line  SPACE: show / hide  ESC: done".to_string();
+                    }
+                    KeyCode::Char('v') if !self.data_series[self.selected_serie].data.is_empty() => {
+                        self.input_mode = InputMode::Cursor;
+                        self.cursor = self.data_series[self.selected_serie].data.len() - 1;
+                        self.cursor_status();
+                    }
                     KeyCode::Char('i') => {
                         self.input_mode = InputMode::Insert;
                         self.input_field = InputField::X;
-                        self.input_x.clear();
-                        self.input_y.clear();
-                        self.status_msg = format!("h: help");
+                        self.clear_inputs();
+                        self.prefill_inputs();
+                        self.status_msg = "h: help".to_string();
                     }
                     KeyCode::Esc => self.mode = ViewMode::Menu,
                     _ => {}
@@ -558,40 +7812,87 @@ impl App {
 
             InputMode::Insert => {
                 match key {
-                    KeyCode::Char(c) if c.is_ascii_digit() || c == '.' || c == '-'=> {
-                        match self.input_field {
-                            InputField::X => {
-                                if self.input_x.len() < 5 {
-                                    self.input_x.push(c);
-                                }
-                            },
-                            InputField::Y => {
-                                if self.input_y.len() < 5 {
-                                    self.input_y.push(c);
-                                }
-                            },
+                    KeyCode::Char(c) if c.is_ascii_digit() || c == '.' || c == '-'
+                        || (self.input_field == InputField::X && (c == ':' || c == ' ')) => {
+                        // X has room for a date and time of day, the boxes widen
+                        let max = if self.input_field == InputField::X { 16 } else { 12 };
+                        let input = self.input_buffer();
+                        if input.len() < max {
+                            input.push(c);
                         }
                     }
                     KeyCode::Backspace => {
-                        match self.input_field {
-                            InputField::X => self.input_x.pop(),
-                            InputField::Y => self.input_y.pop(),
-                        };
+                        self.input_buffer().pop();
                     }
                     KeyCode::Tab => self.cycle_field(),
                     KeyCode::Enter => {
                         self.cycle_field();
-                        if !self.input_y.is_empty() && !self.input_x.is_empty() {
+                        if !self.input_y.is_empty() && !self.input_x.is_empty()
+                            && self.input_values.iter().all(|v| !v.is_empty()) {
                             self.try_insert_point();
                         }
                     }
-                    KeyCode::Left => self.input_field = InputField::X,
-                    KeyCode::Right => self.input_field = InputField::Y,
+                    KeyCode::Left => self.move_field(-1, false),
+                    KeyCode::Right => self.move_field(1, false),
                     KeyCode::Esc => {
                         self.input_mode = InputMode::Normal;
-                        self.input_x.clear();
-                        self.input_y.clear();
-                        self.status_msg = format!("h: help");
+                        self.clear_inputs();
+                        self.status_msg = "h: help".to_string();
+                    }
+                    _ => {}
+                }
+            }
+
+            InputMode::Cursor => {
+                let len = self.data_series[self.selected_serie].data.len();
+                if len == 0 {
+                    self.input_mode = InputMode::Normal;
+                    return;
+                }
+                self.cursor = self.cursor.min(len - 1);
+                let step = self.config.nudge_step;
+                if matches!(key, KeyCode::Up | KeyCode::Down | KeyCode::Char('u')) && !self.writable() {
+                    return;
+                }
+                match key {
+                    KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+                    KeyCode::Right => self.cursor = (self.cursor + 1).min(len - 1),
+                    KeyCode::Home => self.cursor = 0,
+                    KeyCode::End => self.cursor = len - 1,
+                    KeyCode::Up => self.nudge(self.selected_serie, self.cursor, step),
+                    KeyCode::Down => self.nudge(self.selected_serie, self.cursor, -step),
+                    KeyCode::Char('u') => {
+                        self.undo_nudge();
+                        return;
+                    }
+                    KeyCode::Esc | KeyCode::Char('v') => {
+                        self.input_mode = InputMode::Normal;
+                        self.status_msg = "h: help".to_string();
+                        return;
+                    }
+                    _ => {}
+                }
+                self.cursor_status();
+            }
+
+            InputMode::Legend => {
+                let entries = self.legend_entries();
+                match key {
+                    KeyCode::Up | KeyCode::Char('k') => self.legend_idx = self.legend_idx.saturating_sub(1),
+                    KeyCode::Down | KeyCode::Char('j') => self.legend_idx = (self.legend_idx + 1).min(entries.len() - 1),
+                    KeyCode::Char(' ') => {
+                        let name = &entries[self.legend_idx.min(entries.len() - 1)].0;
+                        let hidden = &mut self.hidden;
+                        match hidden.iter().position(|h| h == name) {
+                            Some(i) => {
+                                hidden.remove(i);
+                            }
+                            None => hidden.push(name.clone()),
+                        }
+                    }
+                    KeyCode::Esc | KeyCode::Char('l') => {
+                        self.input_mode = InputMode::Normal;
+                        self.status_msg = "h: help".to_string();
                     }
                     _ => {}
                 }
@@ -599,21 +7900,124 @@ impl App {
         }
     }
 
+    // Shows the last `days` up to the latest point, plain x values count as days
+    fn window_preset(&mut self, days: f64) {
+        let Some(&(last, _)) = self.data_series[self.selected_serie].data.last() else { return };
+        let span = match last >= DATE_MIN {
+            true => days * 86400.0,
+            false => days,
+        };
+        self.x_window = Some((last - span, last));
+        self.status_msg = format!("Last {} days (0: all)", days);
+    }
+
+    // Scales the visible x range around its middle, and the y range once it was
+    // panned, factor below 1 zooms in
+    fn zoom_graph(&mut self, factor: f64) {
+        let [[x0, x1], _] = self.graph_shown;
+        let scale = |(lo, hi): (f64, f64)| {
+            let (mid, half) = ((lo + hi) / 2.0, (hi - lo) / 2.0 * factor);
+            (mid - half, mid + half)
+        };
+        self.x_window = Some(scale(self.x_window.unwrap_or((x0, x1))));
+        self.y_window = self.y_window.map(scale);
+        self.status_msg = "+/-: zoom  ⇆ ⇅: pan  =: fit all".to_string();
+    }
+
+    // Moves the view by fractions of its width and height
+    fn pan_graph(&mut self, dx: f64, dy: f64) {
+        let [[x0, x1], [y0, y1]] = self.graph_shown;
+        let shift = |(lo, hi): (f64, f64), by: f64| (lo + (hi - lo) * by, hi + (hi - lo) * by);
+        if dx != 0.0 {
+            self.x_window = Some(shift(self.x_window.unwrap_or((x0, x1)), dx));
+        }
+        if dy != 0.0 {
+            self.y_window = Some(shift(self.y_window.unwrap_or((y0, y1)), dy));
+        }
+        self.status_msg = "+/-: zoom  ⇆ ⇅: pan  =: fit all".to_string();
+    }
+
+    // Readout of the point under the cursor, with the overlaid series at its x
+    fn cursor_status(&mut self) {
+        let serie = &self.data_series[self.selected_serie];
+        let precision = serie.precision(self.config.precision);
+        let (x, y) = serie.data[self.cursor];
+        let mut readout = format!("x = {}  y = {}", serie.describe_x(x, precision), format_value(y, precision));
+        if !serie.unit.is_empty() {
+            readout += &format!(" {}", serie.unit);
+        }
+        if let Some(error) = serie.error(self.cursor) {
+            readout += &format!(" ± {}", format_value(error, precision));
+        }
+        for (column, v) in serie.extra_columns().iter().zip(serie.values(self.cursor)) {
+            readout += &format!("  {} = {}", column, format_value(*v, precision));
+        }
+        for s in self.overlay_series() {
+            let other = &self.data_series[s];
+            if let Some(v) = interpolate(&other.data, x) {
+                readout += &format!("  {} = {}", other.name, format_value(v, other.precision(self.config.precision)));
+            }
+        }
+        self.status_msg = format!("{}  ⇆: point  ⇅: nudge  u: undo", readout);
+
+        // A zoomed graph follows the cursor
+        if let Some((lo, hi)) = self.x_window && !(lo..=hi).contains(&x) {
+            let shift = if x < lo { x - lo } else { x - hi };
+            self.x_window = Some((lo + shift, hi + shift));
+        }
+    }
+
     fn try_insert_point(&mut self) {
-        match (self.input_x.parse::<f64>(), self.input_y.parse::<f64>()) {
-            (Ok(x), Ok(y)) => {
-                let serie = &mut self.data_series[self.selected_serie];
-                serie.data.push((x, y));
-                serie.data.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        let error = match self.input_error.is_empty() {
+            true => Ok(None),
+            false => self.input_error.parse::<f64>().map(Some),
+        };
 
-                self.input_mode = InputMode::Normal;
-                self.input_x.clear();
-                self.input_y.clear();
-                self.status_msg = format!("Inserted point ({:.2}, {:.2})", x, y);
+        let values = self.input_values.iter().map(|v| v.parse::<f64>()).collect::<Result<Vec<_>, _>>();
+
+        // X may be a date, prefilled for dated series
+        let x = parse_x(&self.input_x).ok_or(());
+        match (x, self.input_y.parse::<f64>(), values, error) {
+            (Ok(x), Ok(y), Ok(values), Ok(error)) if error.is_none_or(|e| e >= 0.0) => {
+                self.insert_checked(vec![(x, y, PointMeta { error, values, ..Default::default() })]);
+            }
+            (Ok(_), Ok(_), Ok(_), _) => {
+                self.status_msg = "Error: ± must be a positive number".to_string();
             }
             _ => {
-                self.status_msg = "Error: enter valid numbers for x and y".to_string();
+                self.status_msg = "Error: enter valid numbers for every field".to_string();
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_like_drops_float_noise() {
+        assert_eq!(round_like(0.7 + 0.1, &[0.7, 0.1]), 0.8);
+        assert_eq!(round_like(0.1 + 0.2, &[0.1, 0.2]), 0.3);
+        assert_eq!(round_like(80.15 - 0.05, &[80.15, 0.05]), 80.1);
+        assert_eq!(round_like(3.0 * 0.1, &[0.1]), 0.3);
+        assert_eq!(round_like(2.0 + 3.0, &[2.0, 3.0]), 5.0);
+    }
+
+    #[test]
+    fn round_like_keeps_what_the_inputs_carry() {
+        assert_eq!(round_like(1.23456, &[1.5, 0.25]), 1.23);
+        assert_eq!(round_like(1e20 + 1.0, &[1e20, 1.0]), 1e20);
+        assert_eq!(round_like(-0.7 - 0.1, &[-0.7, 0.1]), -0.8);
+    }
+
+    // The mean of a merge keeps the extra decimal halving adds
+    #[test]
+    fn merge_mean() {
+        let mean = |a: f64, b: f64| round_like(a + b, &[a, b]) / 2.0;
+        assert_eq!(mean(1.0, 2.0), 1.5);
+        assert_eq!(mean(80.1, 80.2), 80.15);
+        assert_eq!(mean(0.7, 0.1), 0.4);
+        assert_eq!(mean(72.0, 72.0), 72.0);
+    }
+}
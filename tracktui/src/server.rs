@@ -0,0 +1,128 @@
+use std::{
+    error::Error,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+// Request handed to the UI thread, which owns the data and sends back
+// the status code and JSON body of the response
+pub struct ApiRequest {
+    pub method: String,
+    pub path: Vec<String>, // Percent-decoded segments
+    pub body: String,
+    pub reply: Sender<(u16, String)>,
+}
+
+// Accepts connections on a background thread and handles each on one of its
+// own, so a slow client cannot hold up the others. Requests without the
+// bearer token are answered there and never reach the UI
+pub fn start(addr: &str, token: String) -> Result<Receiver<ApiRequest>, Box<dyn Error>> {
+    let listener = TcpListener::bind(addr)?;
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let (token, tx) = (token.clone(), tx.clone());
+            thread::spawn(move || {
+                let _ = handle_connection(stream, &token, &tx);
+            });
+        }
+    });
+    Ok(rx)
+}
+
+fn handle_connection(mut stream: TcpStream, token: &str, tx: &Sender<ApiRequest>) -> Result<(), Box<dyn Error>> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    let mut authorized = false;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else { continue };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => content_length = value.parse().unwrap_or(0),
+            "authorization" => authorized = value.strip_prefix("Bearer ").is_some_and(|t| same_token(t, token)),
+            _ => {}
+        }
+    }
+
+    if !authorized {
+        return respond(&mut stream, 401, r#"{"error":"missing or wrong token"}"#);
+    }
+    if content_length > 64 * 1024 {
+        return respond(&mut stream, 413, r#"{"error":"body too large"}"#);
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let path = target.split('?').next().unwrap_or_default()
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(percent_decode)
+        .collect();
+    let (reply, response) = mpsc::channel();
+    tx.send(ApiRequest { method, path, body: String::from_utf8_lossy(&body).to_string(), reply })?;
+
+    let (status, body) = response.recv_timeout(Duration::from_secs(5))
+        .unwrap_or((503, r#"{"error":"busy"}"#.to_string()));
+    respond(&mut stream, status, &body)
+}
+
+fn respond(stream: &mut TcpStream, status: u16, body: &str) -> Result<(), Box<dyn Error>> {
+    let reason = match status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        413 => "Payload Too Large",
+        _ => "Service Unavailable",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, reason, body.len(), body
+    )?;
+    Ok(())
+}
+
+// Compares in constant time so the token cannot be guessed byte by byte
+fn same_token(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// Only %XX, a + in a path is a plain +
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match (bytes[i], s.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok())) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
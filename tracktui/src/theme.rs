@@ -0,0 +1,109 @@
+use std::{error::Error, fs};
+use serde::{Serialize, Deserialize};
+
+use ratatui::style::Color;
+
+pub const THEMES_DIR: &str = "themes";
+
+// Colors of the UI, read from themes/<name>.toml so a scheme can be shared
+// as one file. Keys left out keep the default's color. Colors are names like
+// light_blue, hex like #ff8800 or terminal palette indexes like 208
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    // Taken in turn by series without a color of their own
+    pub series: Vec<Color>,
+    // Lines of a series' extra columns
+    pub columns: Vec<Color>,
+
+    // Table headers, the field being typed in, popups waiting for an answer,
+    // pins and overdue series
+    pub accent: Color,
+    // Damaged files, failed checks, invalid batch rows, recording and the graph cursor
+    pub alert: Color,
+    pub selected_fg: Color,
+    pub selected_bg: Color,
+
+    // Trend arrows heading for the goal, away from it, or without a goal
+    pub on_track: Color,
+    pub off_track: Color,
+    pub muted: Color, // Also error bars, columns, hidden and archived entries and the previous period in compare
+
+    pub average: Color,
+    pub trend: Color,
+    pub goal: Color, // Also the goal's progress and Reached
+    pub target: Color,
+    pub mean: Color,
+    pub min: Color,
+    pub max: Color,
+    pub spectrum: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            series: vec![Color::Cyan, Color::Yellow, Color::Green, Color::Magenta, Color::Red, Color::Blue],
+            columns: vec![Color::Yellow, Color::Green, Color::Magenta, Color::Red],
+            accent: Color::Yellow,
+            alert: Color::Red,
+            selected_fg: Color::Black,
+            selected_bg: Color::White,
+            on_track: Color::Green,
+            off_track: Color::Red,
+            muted: Color::DarkGray,
+            average: Color::Gray,
+            trend: Color::LightMagenta,
+            goal: Color::LightGreen,
+            target: Color::Green,
+            mean: Color::White,
+            min: Color::LightBlue,
+            max: Color::LightRed,
+            spectrum: Color::Magenta,
+        }
+    }
+}
+
+impl Theme {
+    fn path(name: &str) -> String {
+        format!("{}/{}.toml", THEMES_DIR, name)
+    }
+
+    pub fn load(name: &str) -> Result<Self, Box<dyn Error>> {
+        let theme: Self = toml::from_str(&fs::read_to_string(Self::path(name))?)?;
+        match theme.series.is_empty() || theme.columns.is_empty() {
+            true => Err("series and columns need at least one color".into()),
+            false => Ok(theme),
+        }
+    }
+
+    pub fn save(&self, name: &str) -> Result<String, Box<dyn Error>> {
+        fs::create_dir_all(THEMES_DIR)?;
+        let path = Self::path(name);
+        fs::write(&path, toml::to_string(self)?)?;
+        Ok(path)
+    }
+
+    // Names of the files in THEMES_DIR, sorted
+    pub fn list() -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(THEMES_DIR).into_iter().flatten().flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|e| e == "toml"))
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+            .collect();
+        names.sort();
+        names
+    }
+
+    pub fn series_color(&self, idx: usize) -> Color {
+        self.series[idx % self.series.len()]
+    }
+
+    // Of the mean, min and max lines
+    pub fn references(&self) -> [Color; 3] {
+        [self.mean, self.min, self.max]
+    }
+
+    pub fn column_color(&self, idx: usize) -> Color {
+        self.columns[idx % self.columns.len()]
+    }
+}